@@ -0,0 +1,51 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Enable / disable debug logs inside cache_file module.
+const DEBUG_CACHE_FILE: bool = false;
+
+/// Directory of the cache files, relative to $HOME.
+const CACHE_DIR: &str = ".momentum-quant";
+
+/// Path of the cache file `file_name`, `None` without $HOME.
+pub fn cache_path(file_name: &str) -> Option<PathBuf> {
+    let home = env::var_os("HOME")?;
+    Some(PathBuf::from(home).join(CACHE_DIR).join(file_name))
+}
+
+/// Read a JSON cache file; a missing or unreadable file is empty.
+pub fn read<T: DeserializeOwned + Default>(path: Option<&Path>) -> T {
+    path.and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Write `value` to a JSON cache file.
+pub fn write<T: Serialize>(path: Option<&Path>, value: &T) {
+    let Some(path) = path else {
+        return;
+    };
+    let result = serde_json::to_string_pretty(value)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(path, content))
+                .map_err(|e| e.to_string())
+        });
+
+    // A cache only saves requests, failing to write it is not an error.
+    if let Err(e) = result {
+        debug_cache_file(&format!("[cache_file::write] {}: {e}", path.display()));
+    }
+}
+
+fn debug_cache_file(msg: &str) {
+    if DEBUG_CACHE_FILE {
+        eprintln!("{msg}");
+    }
+}
@@ -0,0 +1,315 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+/// Default network and pool, looked up in the config.
+pub const DEFAULT_NETWORK: &str = "mainnet";
+pub const DEFAULT_POOL: &str = "SUI-USDC";
+
+/// Default swap amount (in smallest unit of the token).
+pub const DEFAULT_SWAP_AMOUNT: u64 = 1_000_000;
+
+/// Default slippage tolerance, in basis points.
+pub const DEFAULT_SLIPPAGE_BPS: u32 = 50;
+
+/// Default maximum number of pools in an automatically found route.
+pub const DEFAULT_MAX_HOPS: usize = 3;
+
+/// Safety factor applied to the simulated gas cost.
+pub const DEFAULT_GAS_SAFETY_MARGIN: f64 = 1.2;
+
+/// Default gas budget cap.
+pub const DEFAULT_MAX_GAS_BUDGET: u64 = 50_000_000;
+
+/// Default factor applied to the reference gas price.
+pub const DEFAULT_GAS_PRICE_MULTIPLIER: f64 = 1.0;
+
+/// Default wait for a checkpoint with --wait-for-checkpoint, in seconds.
+pub const DEFAULT_FINALITY_TIMEOUT_SECS: u64 = 60;
+
+/// Command-line interface of momentum-quant.
+#[derive(Parser, Debug)]
+#[command(name = "momentum-quant", version, about = "Trade on Momentum CLMM pools over Sui gRPC")]
+pub struct Cli {
+    /// TOML config file (defaults to $MOMENTUM_CONFIG or ./momentum.toml).
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Network section of the config to use.
+    #[arg(long, global = true, env = "MOMENTUM_NETWORK", default_value = DEFAULT_NETWORK)]
+    pub network: String,
+
+    /// Sui gRPC endpoint, overrides the configured one.
+    #[arg(long, global = true)]
+    pub rpc_url: Option<String>,
+
+    /// File containing a bech32 `suiprivkey...` key (else $SUI_PRIVATE_KEY).
+    #[arg(long, global = true)]
+    pub key_file: Option<PathBuf>,
+
+    /// Sui keystore file (defaults to ~/.sui/sui_config/sui.keystore).
+    #[arg(long, global = true)]
+    pub keystore: Option<PathBuf>,
+
+    /// Encrypted keystore (defaults to ~/.momentum-quant/keystore.json).
+    #[arg(long, global = true)]
+    pub encrypted_keystore: Option<PathBuf>,
+
+    /// File holding the encrypted keystore passphrase; prompted otherwise.
+    #[arg(long, global = true)]
+    pub passphrase_file: Option<PathBuf>,
+
+    /// Alias or address of the keystore key to sign with.
+    #[arg(long, global = true)]
+    pub key: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Build, sign and execute a swap.
+    Swap(SwapArgs),
+    /// Show the state of a pool and simulate a swap against it.
+    Quote(QuoteArgs),
+    /// Quote every route between two coin types over the configured pools.
+    Route(RouteArgs),
+    /// List coin balances owned by the sender.
+    Balances,
+    /// List SUI gas coins owned by the sender.
+    Gas,
+    /// Merge every coin type's objects into one primary coin per type.
+    Consolidate(ConsolidateArgs),
+    /// Manage the encrypted keystore.
+    Keys(KeysArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct KeysArgs {
+    #[command(subcommand)]
+    pub command: KeysCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum KeysCommand {
+    /// Encrypt and store a key, read from --key-file, $SUI_PRIVATE_KEY or a prompt.
+    Add {
+        /// Name to select the key with --key.
+        #[arg(long)]
+        alias: String,
+
+        /// Generate a new Ed25519 key instead of importing one.
+        #[arg(long)]
+        generate: bool,
+    },
+    /// List stored keys without unlocking them.
+    List,
+    /// Decrypt a key and print it as a bech32 `suiprivkey...` string.
+    Export,
+}
+
+/// Swap direction of a Momentum pool.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Sell token A for token B.
+    AToB,
+    /// Sell token B for token A.
+    BToA,
+}
+
+/// Output format of the trade report.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Human-readable table.
+    Table,
+    /// One JSON object.
+    Json,
+}
+
+impl Direction {
+    /// `true` for A -> B, `false` for B -> A, as expected by `trade::flash_swap`.
+    pub fn a_to_b(self) -> bool {
+        self == Direction::AToB
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct SwapArgs {
+    /// Pool name from the config registry, or pool object id.
+    #[arg(long, default_value = DEFAULT_POOL)]
+    pub pool: String,
+
+    /// Input coin object id; by default the input is assembled from every
+    /// coin of the input type. Unused when selling SUI, which is split from
+    /// the gas coin.
+    #[arg(long)]
+    pub token: Option<String>,
+
+    /// Input coin type of --route and --to, e.g. `0x2::sui::SUI`; defaults
+    /// to the type of the --token coin.
+    #[arg(long)]
+    pub from: Option<String>,
+
+    /// Multi-hop route: comma-separated pools (names or ids) traded in order,
+    /// e.g. `SUI-USDC,USDC-DEEP`; replaces --pool and --direction.
+    #[arg(long, value_delimiter = ',', conflicts_with = "exact_out")]
+    pub route: Vec<String>,
+
+    /// Output coin type; the best route over the configured pools is found
+    /// automatically and replaces --pool, --direction and --route.
+    #[arg(long, conflicts_with_all = ["exact_out", "route"])]
+    pub to: Option<String>,
+
+    /// Maximum number of pools in an automatically found route.
+    #[arg(long, default_value_t = DEFAULT_MAX_HOPS)]
+    pub max_hops: usize,
+
+    /// With --to, split the order across every configured pool of the pair
+    /// instead of routing it through one path.
+    #[arg(long, requires = "to")]
+    pub split: bool,
+
+    /// Input amount, or output amount with --exact-out (in smallest unit of the token).
+    #[arg(long, default_value_t = DEFAULT_SWAP_AMOUNT)]
+    pub amount: u64,
+
+    /// Swap direction.
+    #[arg(long, value_enum, default_value_t = Direction::AToB)]
+    pub direction: Direction,
+
+    /// Buy exactly --amount of the output coin instead of selling --amount.
+    #[arg(long)]
+    pub exact_out: bool,
+
+    /// Slippage tolerance in basis points of the current pool price.
+    #[arg(long, default_value_t = DEFAULT_SLIPPAGE_BPS)]
+    pub slippage_bps: u32,
+
+    /// Minimum output amount; replaces the tolerance-based limits.
+    #[arg(long, conflicts_with_all = ["exact_out", "max_in"])]
+    pub min_out: Option<u64>,
+
+    /// Maximum input amount with --exact-out; replaces the tolerance-based limits.
+    #[arg(long, requires = "exact_out")]
+    pub max_in: Option<u64>,
+
+    /// Simulate the transaction and print its effects without executing it.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Simulate before executing and stop if the simulation fails.
+    #[arg(long)]
+    pub simulate: bool,
+
+    /// Stop before executing if the simulated output is below this amount
+    /// (implies --simulate).
+    #[arg(long)]
+    pub sim_min_out: Option<u64>,
+
+    /// Fixed gas budget in MIST; estimated from a simulation when omitted.
+    #[arg(long)]
+    pub gas_budget: Option<u64>,
+
+    /// Upper bound of the estimated gas budget, in MIST.
+    #[arg(long, default_value_t = DEFAULT_MAX_GAS_BUDGET)]
+    pub max_gas_budget: u64,
+
+    /// Factor applied to the simulated computation + storage cost.
+    #[arg(long, default_value_t = DEFAULT_GAS_SAFETY_MARGIN)]
+    pub gas_margin: f64,
+
+    /// Fixed gas price in MIST; the epoch's reference gas price when omitted.
+    #[arg(long)]
+    pub gas_price: Option<u64>,
+
+    /// Priority factor applied to the reference gas price, e.g. 1.5 when
+    /// the network is congested.
+    #[arg(long, default_value_t = DEFAULT_GAS_PRICE_MULTIPLIER, conflicts_with = "gas_price")]
+    pub gas_price_multiplier: f64,
+
+    /// After execution, wait until the transaction is included in a
+    /// checkpoint before reporting it.
+    #[arg(long)]
+    pub wait_for_checkpoint: bool,
+
+    /// How long --wait-for-checkpoint waits, in seconds.
+    #[arg(long, default_value_t = DEFAULT_FINALITY_TIMEOUT_SECS, requires = "wait_for_checkpoint")]
+    pub finality_timeout_secs: u64,
+
+    /// Format of the trade report printed after execution.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+    pub report: ReportFormat,
+
+    /// Also append the trade report as one JSON line to this file.
+    #[arg(long)]
+    pub report_file: Option<PathBuf>,
+}
+
+impl SwapArgs {
+    /// Budget the transaction is built (and simulated) with.
+    pub fn initial_gas_budget(&self) -> u64 {
+        self.gas_budget.unwrap_or(self.max_gas_budget)
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct QuoteArgs {
+    /// Pool name from the config registry, or pool object id.
+    #[arg(long, default_value = DEFAULT_POOL)]
+    pub pool: String,
+
+    /// Input amount to quote (in smallest unit of the token).
+    #[arg(long, default_value_t = DEFAULT_SWAP_AMOUNT)]
+    pub amount: u64,
+
+    /// Swap direction.
+    #[arg(long, value_enum, default_value_t = Direction::AToB)]
+    pub direction: Direction,
+}
+
+#[derive(Args, Debug)]
+pub struct RouteArgs {
+    /// Input coin type, e.g. `0x2::sui::SUI`.
+    #[arg(long)]
+    pub from: String,
+
+    /// Output coin type.
+    #[arg(long)]
+    pub to: String,
+
+    /// Input amount to route (in smallest unit of the token).
+    #[arg(long, default_value_t = DEFAULT_SWAP_AMOUNT)]
+    pub amount: u64,
+
+    /// Maximum number of pools per route.
+    #[arg(long, default_value_t = DEFAULT_MAX_HOPS)]
+    pub max_hops: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct ConsolidateArgs {
+    /// Only consolidate this coin type, e.g. `0x2::sui::SUI`.
+    #[arg(long)]
+    pub coin_type: Option<String>,
+
+    /// Simulate the merges without executing them.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Upper bound of each merge's estimated gas budget, in MIST.
+    #[arg(long, default_value_t = DEFAULT_MAX_GAS_BUDGET)]
+    pub max_gas_budget: u64,
+
+    /// Factor applied to the simulated computation + storage cost.
+    #[arg(long, default_value_t = DEFAULT_GAS_SAFETY_MARGIN)]
+    pub gas_margin: f64,
+
+    /// Fixed gas price in MIST; the epoch's reference gas price when omitted.
+    #[arg(long)]
+    pub gas_price: Option<u64>,
+
+    /// Priority factor applied to the reference gas price.
+    #[arg(long, default_value_t = DEFAULT_GAS_PRICE_MULTIPLIER, conflicts_with = "gas_price")]
+    pub gas_price_multiplier: f64,
+}
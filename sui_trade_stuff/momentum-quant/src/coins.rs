@@ -0,0 +1,385 @@
+use prost_types::FieldMask;
+use sui_rpc::Client;
+use sui_rpc::proto::sui::rpc::v2::ListOwnedObjectsRequest;
+use sui_sdk_types::{Address, Argument, Digest, Transaction, TypeTag};
+use sui_transaction_builder::TransactionBuilder;
+use sui_transaction_builder::unresolved::Input;
+
+use crate::error::{self, QuantError};
+
+/// Enable / disable debug logs inside coins module.
+const DEBUG_COINS: bool = false;
+
+/// Object type filter of SUI coins.
+pub const SUI_COIN_TYPE: &str = "0x2::coin::Coin<0x2::sui::SUI>";
+
+/// Protocol limit on gas payment objects per transaction.
+pub const MAX_GAS_OBJECTS: usize = 256;
+
+/// With more spare SUI coins than this, the smallest ones are added to the
+/// gas payment so the transaction merges them into the first gas coin.
+const MERGE_COIN_COUNT_THRESHOLD: usize = 20;
+
+/// Most coins merged by one `MergeCoins` command: the protocol caps a
+/// command at 512 arguments, the primary coin included.
+pub const MAX_MERGE_COINS: usize = 511;
+
+/// Rough computation units of a `MergeCoins` transaction: a fixed part
+/// plus one input per merged coin.
+const MERGE_BASE_COMPUTATION_UNITS: u64 = 1_000;
+const MERGE_COIN_COMPUTATION_UNITS: u64 = 100;
+
+/// Rough storage cost of a merge in MIST: the rewritten primary and gas
+/// coins; merged coins are deleted.
+const MERGE_STORAGE_COST: u64 = 3_000_000;
+
+/// Page size used when listing owned coins.
+const COINS_PAGE_SIZE: u32 = 1000;
+
+/// An owned coin with everything needed to use it as a transaction input.
+#[derive(Clone, Debug)]
+pub struct OwnedCoin {
+    pub object_id: Address,
+    pub version: u64,
+    pub digest: Digest,
+    pub balance: u64,
+    /// Full object type, `0x2::coin::Coin<T>`.
+    pub object_type: String,
+}
+
+impl OwnedCoin {
+    pub fn input(&self) -> Input {
+        Input::by_id(self.object_id)
+            .with_owned_kind()
+            .with_version(self.version)
+            .with_digest(self.digest)
+    }
+}
+
+/// List every coin of `coin_object_type` (`0x2::coin::Coin<T>`, or
+/// `0x2::coin::Coin` for all types) owned by `owner`, following pagination.
+pub async fn list_coins(
+    client: &mut Client,
+    owner: &Address,
+    coin_object_type: &str,
+) -> Result<Vec<OwnedCoin>, QuantError> {
+    let mut state_client = client.state_client();
+    let mut coins = Vec::new();
+    let mut page_token = None;
+
+    loop {
+        let mut request = ListOwnedObjectsRequest::default();
+        request.owner = Some(owner.to_string());
+        request.page_size = Some(COINS_PAGE_SIZE);
+        request.page_token = page_token;
+        request.object_type = Some(coin_object_type.to_string());
+        request.read_mask = Some(FieldMask {
+            paths: vec![
+            "object_id".to_string(),
+            "version".to_string(),
+            "digest".to_string(),
+            "object_type".to_string(),
+            "balance".to_string(),
+            ],
+        });
+
+        let response = state_client.list_owned_objects(request).await?.into_inner();
+        for object in response.objects {
+            coins.push(OwnedCoin {
+                object_id: error::parse(
+                    object
+                        .object_id
+                        .as_deref()
+                        .ok_or_else(|| QuantError::invalid_response("Missing object_id for owned coin"))?,
+                    "object id",
+                )?,
+                version: object
+                    .version
+                    .ok_or_else(|| QuantError::invalid_response("Missing version for owned coin"))?,
+                digest: error::parse(
+                    object
+                        .digest
+                        .as_deref()
+                        .ok_or_else(|| QuantError::invalid_response("Missing digest for owned coin"))?,
+                    "object digest",
+                )?,
+                balance: object.balance.unwrap_or(0),
+                object_type: object.object_type.unwrap_or_default(),
+            });
+        }
+
+        match response.next_page_token {
+            Some(token) if !token.is_empty() => page_token = Some(token),
+            _ => break,
+        }
+    }
+
+    debug_coins(&format!("[list_coins] {coin_object_type}: {} coins", coins.len()));
+    Ok(coins)
+}
+
+/// Pick SUI coins whose balances cover `budget`.
+///
+/// The largest coins are taken first so the payment uses few objects. When
+/// the wallet holds many spare coins, the smallest of them are added as
+/// well: the transaction merges every gas object into the first one.
+pub fn select_gas_coins(mut coins: Vec<OwnedCoin>, budget: u64) -> Result<Vec<OwnedCoin>, QuantError> {
+    coins.retain(|coin| coin.balance > 0);
+    coins.sort_by_key(|coin| std::cmp::Reverse(coin.balance));
+
+    let mut covered: u64 = 0;
+    let mut count = 0;
+    for coin in coins.iter().take(MAX_GAS_OBJECTS) {
+        if covered >= budget {
+            break;
+        }
+        covered = covered.saturating_add(coin.balance);
+        count += 1;
+    }
+    if covered < budget {
+        let total: u64 = coins.iter().map(|coin| coin.balance).sum();
+        return Err(if total < budget {
+            QuantError::InsufficientBalance {
+                coin_type: SUI_COIN_TYPE.to_string(),
+                required: budget,
+                available: total,
+                coins: coins.len(),
+            }
+        } else {
+            QuantError::FragmentedBalance {
+                coin_type: SUI_COIN_TYPE.to_string(),
+                required: budget,
+                usable: covered,
+                coins: count,
+            }
+        });
+    }
+
+    let mut spare = coins.split_off(count);
+    let mut selected = coins;
+    if spare.len() > MERGE_COIN_COUNT_THRESHOLD {
+        // Smallest first: merging dust frees the most objects per slot.
+        spare.reverse();
+        let room = MAX_GAS_OBJECTS - selected.len();
+        debug_coins(&format!(
+            "[select_gas_coins] merging {} of {} spare coins",
+            spare.len().min(room),
+            spare.len()
+        ));
+        selected.extend(spare.into_iter().take(room));
+    }
+
+    debug_coins(&format!(
+        "[select_gas_coins] budget={budget}, selected={}",
+        selected.len()
+    ));
+    Ok(selected)
+}
+
+/// Pick coins of `coin_type` whose balances cover `amount`, largest first.
+///
+/// A swap merges the picked coins into the first one before splitting the
+/// input off, so at most `MAX_MERGE_COINS + 1` coins can be used.
+pub fn select_input_coins(
+    mut coins: Vec<OwnedCoin>,
+    amount: u64,
+    coin_type: &TypeTag,
+) -> Result<Vec<OwnedCoin>, QuantError> {
+    coins.retain(|coin| coin.balance > 0);
+    coins.sort_by_key(|coin| std::cmp::Reverse(coin.balance));
+
+    let total: u64 = coins.iter().map(|coin| coin.balance).sum();
+    if total < amount {
+        return Err(QuantError::InsufficientBalance {
+            coin_type: coin_type.to_string(),
+            required: amount,
+            available: total,
+            coins: coins.len(),
+        });
+    }
+
+    let mut covered: u64 = 0;
+    let mut count = 0;
+    for coin in coins.iter().take(MAX_MERGE_COINS + 1) {
+        if covered >= amount {
+            break;
+        }
+        covered = covered.saturating_add(coin.balance);
+        count += 1;
+    }
+    if covered < amount {
+        return Err(QuantError::FragmentedBalance {
+            coin_type: coin_type.to_string(),
+            required: amount,
+            usable: covered,
+            coins: count,
+        });
+    }
+
+    coins.truncate(count);
+    debug_coins(&format!(
+        "[select_input_coins] amount={amount}, selected={count}, covered={covered}"
+    ));
+    Ok(coins)
+}
+
+/// Rough gas cost of merging `coin_count` coins at `gas_price`, in MIST,
+/// to select gas coins before the merge is simulated.
+pub fn estimate_merge_gas(coin_count: usize, gas_price: u64) -> u64 {
+    let units = MERGE_BASE_COMPUTATION_UNITS
+        .saturating_add(MERGE_COIN_COMPUTATION_UNITS.saturating_mul(coin_count as u64));
+    MERGE_STORAGE_COST.saturating_add(units.saturating_mul(gas_price))
+}
+
+/// Build a transaction merging `coins` into `primary` with one `MergeCoins`
+/// command.
+///
+/// With `primary` set to `None` the coins are merged into the gas coin,
+/// which is how SUI itself is consolidated.
+pub fn create_merge_transaction(
+    primary: Option<&OwnedCoin>,
+    coins: &[OwnedCoin],
+    gas_inputs: Vec<Input>,
+    sender: Address,
+    gas_budget: u64,
+    gas_price: u64,
+) -> Result<Transaction, QuantError> {
+    if coins.is_empty() || coins.len() > MAX_MERGE_COINS {
+        return Err(QuantError::InvalidInput(format!(
+            "A merge takes 1 to {MAX_MERGE_COINS} coins, got {}",
+            coins.len()
+        )));
+    }
+    debug_coins(&format!("[create_merge_transaction] merging {} coins", coins.len()));
+
+    let mut tx = TransactionBuilder::new();
+    tx.set_sender(sender);
+    tx.set_gas_budget(gas_budget);
+    tx.set_gas_price(gas_price);
+    tx.add_gas_objects(gas_inputs);
+
+    let target = match primary {
+        Some(primary) => tx.input(primary.input()),
+        None => Argument::Gas,
+    };
+    let sources: Vec<Argument> = coins.iter().map(|coin| tx.input(coin.input())).collect();
+    tx.merge_coins(target, sources);
+
+    tx.finish().map_err(|e| QuantError::Build(e.to_string()))
+}
+
+fn debug_coins(msg: &str) {
+    if DEBUG_COINS {
+        eprintln!("{msg}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coin(id: u8, balance: u64) -> OwnedCoin {
+        OwnedCoin {
+            object_id: Address::new([id; 32]),
+            version: 1,
+            digest: Digest::new([id; 32]),
+            balance,
+            object_type: SUI_COIN_TYPE.to_string(),
+        }
+    }
+
+    fn balances(coins: &[OwnedCoin]) -> Vec<u64> {
+        coins.iter().map(|coin| coin.balance).collect()
+    }
+
+    #[test]
+    fn select_gas_coins_takes_the_largest_first() {
+        let selected = select_gas_coins(vec![coin(1, 5), coin(2, 100), coin(3, 0), coin(4, 50)], 120).unwrap();
+        assert_eq!(balances(&selected), vec![100, 50]);
+    }
+
+    #[test]
+    fn select_gas_coins_merges_many_spare_coins() {
+        // One coin covers the budget, 29 spare coins exceed the threshold.
+        let coins: Vec<OwnedCoin> = (1..=30).map(|i| coin(i, if i == 1 { 1_000 } else { i as u64 })).collect();
+        let selected = select_gas_coins(coins, 500).unwrap();
+        assert_eq!(selected.len(), 30);
+        assert_eq!(selected[0].balance, 1_000);
+        assert_eq!(selected[1].balance, 2);
+        assert_eq!(selected[29].balance, 30);
+    }
+
+    #[test]
+    fn select_gas_coins_leaves_few_spare_coins() {
+        let coins: Vec<OwnedCoin> = (1..=10).map(|i| coin(i, i as u64 * 100)).collect();
+        let selected = select_gas_coins(coins, 1_500).unwrap();
+        assert_eq!(balances(&selected), vec![1_000, 900]);
+    }
+
+    #[test]
+    fn select_gas_coins_reports_a_short_balance() {
+        match select_gas_coins(vec![coin(1, 10), coin(2, 0)], 20) {
+            Err(QuantError::InsufficientBalance { required, available, coins, .. }) => {
+                assert_eq!((required, available, coins), (20, 10, 1));
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn select_gas_coins_reports_a_fragmented_balance() {
+        let coins: Vec<OwnedCoin> = (0..300).map(|i| coin(i as u8, 1)).collect();
+        match select_gas_coins(coins, 280) {
+            Err(QuantError::FragmentedBalance { usable, coins, .. }) => {
+                assert_eq!((usable, coins), (MAX_GAS_OBJECTS as u64, MAX_GAS_OBJECTS));
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    fn usdc() -> TypeTag {
+        "0xdba3::usdc::USDC".parse().unwrap()
+    }
+
+    #[test]
+    fn select_input_coins_takes_the_largest_first() {
+        let selected = select_input_coins(vec![coin(1, 3), coin(2, 10), coin(3, 0), coin(4, 7)], 15, &usdc()).unwrap();
+        assert_eq!(balances(&selected), vec![10, 7]);
+    }
+
+    #[test]
+    fn select_input_coins_assembles_many_coins() {
+        let coins: Vec<OwnedCoin> = (0..400).map(|i| coin(i as u8, 1)).collect();
+        let selected = select_input_coins(coins, 350, &usdc()).unwrap();
+        assert_eq!(selected.len(), 350);
+    }
+
+    #[test]
+    fn select_input_coins_reports_a_short_balance() {
+        match select_input_coins(vec![coin(1, 3), coin(2, 4)], 10, &usdc()) {
+            Err(QuantError::InsufficientBalance { coin_type, required, available, coins }) => {
+                assert_eq!(coin_type, usdc().to_string());
+                assert_eq!((required, available, coins), (10, 7, 2));
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn select_input_coins_reports_a_fragmented_balance() {
+        let coins: Vec<OwnedCoin> = (0..600).map(|i| coin(i as u8, 1)).collect();
+        match select_input_coins(coins, 550, &usdc()) {
+            Err(QuantError::FragmentedBalance { usable, coins, .. }) => {
+                assert_eq!((usable, coins), (MAX_MERGE_COINS as u64 + 1, MAX_MERGE_COINS + 1));
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn estimate_merge_gas_grows_with_the_merged_coins() {
+        assert_eq!(estimate_merge_gas(1, 1_000), 3_000_000 + 1_100 * 1_000);
+        assert!(estimate_merge_gas(MAX_MERGE_COINS, 1_000) > estimate_merge_gas(10, 1_000));
+        assert_eq!(estimate_merge_gas(1, u64::MAX), u64::MAX);
+    }
+}
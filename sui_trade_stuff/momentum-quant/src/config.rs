@@ -0,0 +1,351 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use sui_sdk_types::Address;
+
+use crate::error::QuantError;
+
+/// Config file looked up in the working directory when no path is given.
+const DEFAULT_CONFIG_FILE: &str = "momentum.toml";
+
+/// Environment variables overriding the selected network.
+const ENV_CONFIG_PATH: &str = "MOMENTUM_CONFIG";
+const ENV_RPC_URL: &str = "MOMENTUM_RPC_URL";
+const ENV_TRADE_PACKAGE: &str = "MOMENTUM_TRADE_PACKAGE";
+const ENV_SLIPPAGE_PACKAGE: &str = "MOMENTUM_SLIPPAGE_PACKAGE";
+const ENV_VERSIONED_OBJECT: &str = "MOMENTUM_VERSIONED_OBJECT";
+
+/// Built-in mainnet defaults, used when no config file overrides them.
+const MAINNET_RPC_URL: &str = "http://3.114.103.176:443";
+const MAINNET_TRADE_PACKAGE: &str =
+    "0xcf60a40f45d46fc1e828871a647c1e25a0915dec860d2662eb10fdb382c3c1d1";
+const MAINNET_SLIPPAGE_PACKAGE: &str =
+    "0x8add2f0f8bc9748687639d7eb59b2172ba09a0172d9e63c029e23a7dbdb6abe6";
+const MAINNET_VERSIONED_OBJECT: &str =
+    "0x2375a0b1ec12010aaea3b2545acfa2ad34cfbba03ce4b59f4c39e1e25eed1b2a";
+const MAINNET_SUI_USDC_POOL: &str =
+    "0x455cf8d2ac91e7cb883f515874af750ed3cd18195c970b7a2d46235ac2b0c388";
+
+/// Default rough gas cost of a route, in MIST: a fixed part plus one flash
+/// swap / repay sequence per hop.
+const DEFAULT_ROUTE_BASE_GAS: u64 = 1_000_000;
+const DEFAULT_ROUTE_HOP_GAS: u64 = 1_500_000;
+
+const SUI_TYPE: &str = "0x2::sui::SUI";
+const USDC_TYPE: &str =
+    "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC";
+
+/// A set of named networks.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub networks: BTreeMap<String, NetworkConfig>,
+}
+
+/// Endpoints, Momentum packages and pool registry of one network.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkConfig {
+    /// Network name the config was selected by, e.g. `mainnet`.
+    pub name: String,
+    /// gRPC endpoints, tried in order until one answers.
+    pub rpc_urls: Vec<String>,
+    pub packages: PackageConfig,
+    /// Pools by name, e.g. `SUI-USDC`.
+    pub pools: BTreeMap<String, PoolConfig>,
+    /// Readable reasons of the trade package's abort codes.
+    pub abort_reasons: Vec<AbortReasonConfig>,
+    /// Gas cost routes are ranked with.
+    pub route_gas: RouteGasConfig,
+}
+
+/// Momentum package ids and global objects.
+#[derive(Debug, Clone, Default)]
+pub struct PackageConfig {
+    /// Package exposing `trade::flash_swap` / `trade::repay_flash_swap`.
+    pub trade: String,
+    /// Package exposing `slippage_check::assert_slippage`.
+    pub slippage: String,
+    /// First published ids of `trade` and `slippage`, which Move abort
+    /// locations report after an upgrade.
+    pub trade_original: Option<String>,
+    pub slippage_original: Option<String>,
+    /// Momentum versioned (global config) shared object.
+    pub versioned_object: String,
+}
+
+/// Gas cost of a route in MIST, `base + per_hop * hops`, charged against
+/// its output when routes are ranked.
+#[derive(Debug, Clone)]
+pub struct RouteGasConfig {
+    pub base: u64,
+    pub per_hop: u64,
+}
+
+impl Default for RouteGasConfig {
+    fn default() -> RouteGasConfig {
+        RouteGasConfig {
+            base: DEFAULT_ROUTE_BASE_GAS,
+            per_hop: DEFAULT_ROUTE_HOP_GAS,
+        }
+    }
+}
+
+impl RouteGasConfig {
+    /// Gas cost of a route of `hops` pools.
+    pub fn cost(&self, hops: usize) -> u64 {
+        self.base.saturating_add(self.per_hop.saturating_mul(hops as u64))
+    }
+}
+
+/// Config file: networks whose fields override the built-in ones.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub networks: BTreeMap<String, NetworkFile>,
+}
+
+/// Fields a config file sets for one network; unset ones keep the
+/// built-in values.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct NetworkFile {
+    pub rpc_urls: Option<Vec<String>>,
+    #[serde(default)]
+    pub packages: PackageFile,
+    #[serde(default)]
+    pub pools: BTreeMap<String, PoolConfig>,
+    #[serde(default)]
+    pub abort_reasons: Vec<AbortReasonConfig>,
+    #[serde(default)]
+    pub route_gas: RouteGasFile,
+}
+
+/// Route gas costs a config file sets.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct RouteGasFile {
+    pub base: Option<u64>,
+    pub per_hop: Option<u64>,
+}
+
+/// Package ids a config file sets.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PackageFile {
+    pub trade: Option<String>,
+    pub slippage: Option<String>,
+    pub trade_original: Option<String>,
+    pub slippage_original: Option<String>,
+    pub versioned_object: Option<String>,
+}
+
+/// Reason of the abort `code` in the trade package's `module`, e.g.
+/// `pool` / `trade`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AbortReasonConfig {
+    pub module: String,
+    pub code: u64,
+    pub reason: String,
+}
+
+/// A Momentum pool and its coin types.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PoolConfig {
+    pub id: String,
+    pub coin_type_a: String,
+    pub coin_type_b: String,
+}
+
+impl Config {
+    /// Load the built-in defaults, layer the config file on top of them and
+    /// return the requested network with environment overrides applied.
+    ///
+    /// The file is `path` if given, else `$MOMENTUM_CONFIG`, else
+    /// `momentum.toml` in the working directory if it exists.
+    pub fn load_network(
+        path: Option<&Path>,
+        network: &str,
+    ) -> Result<NetworkConfig, QuantError> {
+        let mut config = Config::builtin();
+
+        if let Some(path) = config_path(path) {
+            config.merge(ConfigFile::from_file(&path)?);
+        }
+
+        let mut network_config = config
+            .networks
+            .remove(network)
+            .ok_or_else(|| QuantError::Config(format!("Unknown network '{network}' in config")))?;
+        network_config.name = network.to_string();
+        network_config.apply_env();
+        network_config.validate()?;
+        Ok(network_config)
+    }
+
+    /// Layer `file` over the networks: packages and route gas field by
+    /// field, pools by name, abort reasons by module and code, then the URL
+    /// list. Unknown networks start empty.
+    fn merge(&mut self, file: ConfigFile) {
+        for (name, overrides) in file.networks {
+            let network = self.networks.entry(name).or_default();
+            let packages = overrides.packages;
+            if let Some(trade) = packages.trade {
+                network.packages.trade = trade;
+            }
+            if let Some(slippage) = packages.slippage {
+                network.packages.slippage = slippage;
+            }
+            if packages.trade_original.is_some() {
+                network.packages.trade_original = packages.trade_original;
+            }
+            if packages.slippage_original.is_some() {
+                network.packages.slippage_original = packages.slippage_original;
+            }
+            if let Some(versioned_object) = packages.versioned_object {
+                network.packages.versioned_object = versioned_object;
+            }
+            if let Some(base) = overrides.route_gas.base {
+                network.route_gas.base = base;
+            }
+            if let Some(per_hop) = overrides.route_gas.per_hop {
+                network.route_gas.per_hop = per_hop;
+            }
+            network.pools.extend(overrides.pools);
+            // File reasons come first so they win over earlier ones.
+            network.abort_reasons.splice(0..0, overrides.abort_reasons);
+            if let Some(rpc_urls) = overrides.rpc_urls {
+                network.rpc_urls = rpc_urls;
+            }
+        }
+    }
+}
+
+impl ConfigFile {
+    /// Parse a TOML config file.
+    pub fn from_file(path: &Path) -> Result<ConfigFile, QuantError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| {
+                QuantError::Config(format!("Failed to read config {}: {e}", path.display()))
+            })?;
+        let config: ConfigFile = toml::from_str(&content)
+            .map_err(|e| {
+                QuantError::Config(format!("Failed to parse config {}: {e}", path.display()))
+            })?;
+        Ok(config)
+    }
+}
+
+impl Config {
+    /// Defaults matching the values that used to be compiled in.
+    fn builtin() -> Config {
+        let mut pools = BTreeMap::new();
+        pools.insert(
+            "SUI-USDC".to_string(),
+            PoolConfig {
+                id: MAINNET_SUI_USDC_POOL.to_string(),
+                coin_type_a: SUI_TYPE.to_string(),
+                coin_type_b: USDC_TYPE.to_string(),
+            },
+        );
+
+        let mut networks = BTreeMap::new();
+        networks.insert(
+            "mainnet".to_string(),
+            NetworkConfig {
+                name: String::new(),
+                rpc_urls: vec![MAINNET_RPC_URL.to_string()],
+                packages: PackageConfig {
+                    trade: MAINNET_TRADE_PACKAGE.to_string(),
+                    slippage: MAINNET_SLIPPAGE_PACKAGE.to_string(),
+                    trade_original: None,
+                    slippage_original: None,
+                    versioned_object: MAINNET_VERSIONED_OBJECT.to_string(),
+                },
+                pools,
+                abort_reasons: Vec::new(),
+                route_gas: RouteGasConfig::default(),
+            },
+        );
+
+        Config { networks }
+    }
+}
+
+impl NetworkConfig {
+    /// Find a pool by registry name or by object id.
+    pub fn find_pool(&self, name_or_id: &str) -> Option<&PoolConfig> {
+        self.pools.get(name_or_id).or_else(|| {
+            self.pools
+                .values()
+                .find(|pool| pool.id.eq_ignore_ascii_case(name_or_id))
+        })
+    }
+
+    /// Object id of a registry pool, or `name_or_id` itself if it is not a
+    /// registry name.
+    pub fn pool_id<'a>(&'a self, name_or_id: &'a str) -> &'a str {
+        self.pools
+            .get(name_or_id)
+            .map(|pool| pool.id.as_str())
+            .unwrap_or(name_or_id)
+    }
+
+    /// At least one endpoint and every package id set to a non-zero id; the
+    /// example config ships zero placeholders for testnet and localnet.
+    fn validate(&self) -> Result<(), QuantError> {
+        if self.rpc_urls.is_empty() {
+            return Err(QuantError::Config(format!(
+                "No rpc_urls configured for network '{}'",
+                self.name
+            )));
+        }
+        let packages = [
+            ("packages.trade", &self.packages.trade),
+            ("packages.slippage", &self.packages.slippage),
+            ("packages.versioned_object", &self.packages.versioned_object),
+        ];
+        for (field, id) in packages {
+            if id.is_empty() {
+                return Err(QuantError::Config(format!(
+                    "No {field} configured for network '{}'",
+                    self.name
+                )));
+            }
+            let address: Address = id.parse().map_err(|e| {
+                QuantError::Config(format!("Invalid {field} '{id}' for network '{}': {e}", self.name))
+            })?;
+            if address == Address::ZERO {
+                return Err(QuantError::Config(format!(
+                    "{field} of network '{}' is the zero placeholder, set the deployed id in the config file",
+                    self.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_env(&mut self) {
+        if let Ok(url) = env::var(ENV_RPC_URL) {
+            self.rpc_urls.insert(0, url);
+        }
+        if let Ok(trade) = env::var(ENV_TRADE_PACKAGE) {
+            self.packages.trade = trade;
+        }
+        if let Ok(slippage) = env::var(ENV_SLIPPAGE_PACKAGE) {
+            self.packages.slippage = slippage;
+        }
+        if let Ok(versioned_object) = env::var(ENV_VERSIONED_OBJECT) {
+            self.packages.versioned_object = versioned_object;
+        }
+    }
+}
+
+fn config_path(path: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = path {
+        return Some(path.to_path_buf());
+    }
+    if let Ok(path) = env::var(ENV_CONFIG_PATH) {
+        return Some(PathBuf::from(path));
+    }
+    let default = PathBuf::from(DEFAULT_CONFIG_FILE);
+    default.exists().then_some(default)
+}
@@ -0,0 +1,419 @@
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::error::QuantError;
+use crate::signer::SuiKeyPair;
+
+/// Encrypted keystore file, relative to $HOME.
+const DEFAULT_ENCRYPTED_KEYSTORE_PATH: &str = ".momentum-quant/keystore.json";
+
+/// Current file format version.
+const KEYSTORE_VERSION: u32 = 1;
+
+/// Argon2id parameters for newly added keys (OWASP recommendation).
+const ARGON2_M_COST: u32 = 19_456;
+const ARGON2_T_COST: u32 = 2;
+const ARGON2_P_COST: u32 = 1;
+
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+const KEY_LENGTH: usize = 32;
+
+/// Keystore whose private keys are encrypted with a passphrase-derived key.
+///
+/// Each entry is `XChaCha20-Poly1305(argon2id(passphrase, salt), flag || key)`,
+/// with the address as associated data. Aliases, addresses and schemes are
+/// stored in clear so `keys list` works without a passphrase.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncryptedKeystore {
+    version: u32,
+    keys: Vec<EncryptedKey>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EncryptedKey {
+    pub alias: String,
+    pub address: String,
+    pub scheme: String,
+    kdf: KdfParams,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct KdfParams {
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl EncryptedKeystore {
+    /// Read the keystore, or start an empty one if the file does not exist.
+    pub fn load_or_default(path: &Path) -> Result<EncryptedKeystore, QuantError> {
+        if !path.exists() {
+            return Ok(EncryptedKeystore {
+                version: KEYSTORE_VERSION,
+                keys: Vec::new(),
+            });
+        }
+        EncryptedKeystore::load(path)
+    }
+
+    pub fn load(path: &Path) -> Result<EncryptedKeystore, QuantError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| {
+                QuantError::Key(format!("Failed to read encrypted keystore {}: {e}", path.display()))
+            })?;
+        let keystore: EncryptedKeystore = serde_json::from_str(&content)
+            .map_err(|e| {
+                QuantError::Key(format!("Malformed encrypted keystore {}: {e}", path.display()))
+            })?;
+
+        if keystore.version != KEYSTORE_VERSION {
+            return Err(QuantError::Key(format!(
+                "Unsupported encrypted keystore version {} in {}",
+                keystore.version,
+                path.display()
+            )));
+        }
+        Ok(keystore)
+    }
+
+    /// Write the keystore, readable by the owner only.
+    ///
+    /// The content goes to a new temporary file next to `path`, which then
+    /// replaces it: an interrupted write leaves the old keystore intact.
+    pub fn save(&self, path: &Path) -> Result<(), QuantError> {
+        let content = serde_json::to_string_pretty(self)?;
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let file_name = path.file_name().ok_or_else(|| {
+            QuantError::Key(format!("Invalid encrypted keystore path {}", path.display()))
+        })?;
+        let mut temp_name = file_name.to_os_string();
+        temp_name.push(format!(".{}.tmp", std::process::id()));
+        let temp_path = dir.join(temp_name);
+
+        let result = fs::create_dir_all(dir)
+            .and_then(|_| write_private_file(&temp_path, content.as_bytes()))
+            .and_then(|_| fs::rename(&temp_path, path))
+            .and_then(|_| sync_dir(dir));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result.map_err(|e| {
+            QuantError::Key(format!("Failed to write encrypted keystore {}: {e}", path.display()))
+        })
+    }
+
+    pub fn keys(&self) -> &[EncryptedKey] {
+        &self.keys
+    }
+
+    /// Encrypt `flag || key` bytes under `passphrase` and add them as `alias`.
+    pub fn add(
+        &mut self,
+        alias: &str,
+        flagged_key: &[u8],
+        passphrase: &str,
+    ) -> Result<&EncryptedKey, QuantError> {
+        let key_pair = SuiKeyPair::from_flagged_bytes(flagged_key)?;
+        let address = key_pair.derive_address().to_string();
+
+        if let Some(existing) = self
+            .keys
+            .iter()
+            .find(|key| key.alias == alias || key.address == address)
+        {
+            return Err(QuantError::Key(format!(
+                "Key {} ({}) is already in the keystore",
+                existing.alias, existing.address
+            )));
+        }
+
+        let mut salt = [0u8; SALT_LENGTH];
+        let mut nonce = [0u8; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let kdf = KdfParams {
+            salt: BASE64.encode(salt),
+            m_cost: ARGON2_M_COST,
+            t_cost: ARGON2_T_COST,
+            p_cost: ARGON2_P_COST,
+        };
+
+        let mut encryption_key = derive_key(passphrase, &kdf)?;
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&encryption_key));
+        encryption_key.zeroize();
+
+        let ciphertext = cipher
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: flagged_key,
+                    aad: address.as_bytes(),
+                },
+            )
+            .map_err(|_| QuantError::Key("Failed to encrypt private key".to_string()))?;
+
+        self.keys.push(EncryptedKey {
+            alias: alias.to_string(),
+            address,
+            scheme: key_pair.scheme().to_string(),
+            kdf,
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        });
+        Ok(self.keys.last().unwrap())
+    }
+
+    /// Find a key by alias or address. Without a selector the keystore must
+    /// hold exactly one key.
+    pub fn find(&self, select: Option<&str>) -> Result<&EncryptedKey, QuantError> {
+        let Some(select) = select else {
+            return match self.keys.as_slice() {
+                [] => Err(QuantError::Key(
+                    "Encrypted keystore is empty, add a key with `keys add`".to_string(),
+                )),
+                [key] => Ok(key),
+                keys => Err(QuantError::Key(format!(
+                    "Encrypted keystore holds {} keys, select one with --key <alias|address>",
+                    keys.len()
+                ))),
+            };
+        };
+
+        self.keys
+            .iter()
+            .find(|key| key.alias == select || key.address.eq_ignore_ascii_case(select))
+            .ok_or_else(|| QuantError::Key(format!("Key {select} not found in encrypted keystore")))
+    }
+}
+
+impl EncryptedKey {
+    /// Decrypt the `flag || key` bytes. Callers must zeroize the result.
+    pub fn decrypt(&self, passphrase: &str) -> Result<Vec<u8>, QuantError> {
+        let nonce = BASE64
+            .decode(&self.nonce)
+            .map_err(|e| QuantError::Key(format!("Malformed nonce for key {}: {e}", self.alias)))?;
+        let ciphertext = BASE64
+            .decode(&self.ciphertext)
+            .map_err(|e| {
+                QuantError::Key(format!("Malformed ciphertext for key {}: {e}", self.alias))
+            })?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(QuantError::Key(format!("Malformed nonce for key {}", self.alias)));
+        }
+
+        let mut encryption_key = derive_key(passphrase, &self.kdf)?;
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&encryption_key));
+        encryption_key.zeroize();
+
+        cipher
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: self.address.as_bytes(),
+                },
+            )
+            .map_err(|_| {
+                QuantError::Key(format!("Wrong passphrase or corrupted key {}", self.alias))
+            })
+    }
+
+    /// Decrypt and build the signer.
+    pub fn unlock(&self, passphrase: &str) -> Result<SuiKeyPair, QuantError> {
+        let mut flagged_key = self.decrypt(passphrase)?;
+        let key_pair = SuiKeyPair::from_flagged_bytes(&flagged_key);
+        flagged_key.zeroize();
+        key_pair
+    }
+}
+
+fn derive_key(passphrase: &str, kdf: &KdfParams) -> Result<[u8; KEY_LENGTH], QuantError> {
+    let salt = BASE64
+        .decode(&kdf.salt)
+        .map_err(|e| QuantError::Key(format!("Malformed salt: {e}")))?;
+    let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(KEY_LENGTH))
+        .map_err(|e| QuantError::Key(format!("Invalid argon2 parameters: {e}")))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut key = [0u8; KEY_LENGTH];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| QuantError::Key(format!("Failed to derive encryption key: {e}")))?;
+    Ok(key)
+}
+
+/// Create `path` with mode 0o600 and write `content` to disk.
+fn write_private_file(path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(content)?;
+    file.sync_all()
+}
+
+/// Flush a rename in `dir` to disk.
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Read the passphrase from `passphrase_file`, else prompt on the terminal.
+pub fn read_passphrase(passphrase_file: Option<&Path>, prompt: &str) -> Result<String, QuantError> {
+    if let Some(path) = passphrase_file {
+        let content = fs::read_to_string(path)
+            .map_err(|e| {
+                QuantError::Key(format!("Failed to read passphrase file {}: {e}", path.display()))
+            })?;
+        return Ok(content.trim_end_matches(['\r', '\n']).to_string());
+    }
+    Ok(rpassword::prompt_password(prompt)?)
+}
+
+/// Read a new passphrase from `passphrase_file`, else prompt twice on the terminal.
+pub fn read_new_passphrase(passphrase_file: Option<&Path>) -> Result<String, QuantError> {
+    let passphrase = read_passphrase(passphrase_file, "New passphrase: ")?;
+    if passphrase.is_empty() {
+        return Err(QuantError::Key("Passphrase must not be empty".to_string()));
+    }
+    if passphrase_file.is_some() {
+        return Ok(passphrase);
+    }
+
+    let confirmation = rpassword::prompt_password("Repeat passphrase: ")?;
+    if passphrase != confirmation {
+        return Err(QuantError::Key("Passphrases do not match".to_string()));
+    }
+    Ok(passphrase)
+}
+
+/// Generate a fresh `flag || key` Ed25519 private key.
+pub fn generate_ed25519_key() -> Vec<u8> {
+    let mut flagged_key = vec![0u8; 1 + KEY_LENGTH];
+    OsRng.fill_bytes(&mut flagged_key[1..]);
+    flagged_key
+}
+
+pub fn default_encrypted_keystore_path() -> Option<PathBuf> {
+    let home = env::var_os("HOME")?;
+    Some(PathBuf::from(home).join(DEFAULT_ENCRYPTED_KEYSTORE_PATH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_keystore() -> EncryptedKeystore {
+        EncryptedKeystore {
+            version: KEYSTORE_VERSION,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Scratch file under the temp directory, unique to this process.
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("momentum-quant-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn add_and_decrypt_round_trip() {
+        let flagged_key = generate_ed25519_key();
+        let mut keystore = empty_keystore();
+        let address = keystore.add("main", &flagged_key, "secret").unwrap().address.clone();
+
+        let key = keystore.find(None).unwrap();
+        assert_eq!(key.decrypt("secret").unwrap(), flagged_key);
+        assert_eq!(key.unlock("secret").unwrap().derive_address().to_string(), address);
+        assert!(key.decrypt("wrong").is_err());
+    }
+
+    #[test]
+    fn add_rejects_duplicates() {
+        let flagged_key = generate_ed25519_key();
+        let mut keystore = empty_keystore();
+        keystore.add("main", &flagged_key, "secret").unwrap();
+        assert!(keystore.add("other", &flagged_key, "secret").is_err());
+        assert!(keystore.add("main", &generate_ed25519_key(), "secret").is_err());
+    }
+
+    #[test]
+    fn find_selects_by_alias_or_address() {
+        let mut keystore = empty_keystore();
+        assert!(keystore.find(None).is_err());
+
+        keystore.add("first", &generate_ed25519_key(), "secret").unwrap();
+        let address = keystore.add("second", &generate_ed25519_key(), "secret").unwrap().address.clone();
+        assert!(keystore.find(None).is_err());
+        assert_eq!(keystore.find(Some("first")).unwrap().alias, "first");
+        assert_eq!(keystore.find(Some(&address.to_uppercase())).unwrap().alias, "second");
+        assert!(keystore.find(Some("third")).is_err());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = temp_path("keystore.json");
+        let flagged_key = generate_ed25519_key();
+        let mut keystore = empty_keystore();
+        keystore.add("main", &flagged_key, "secret").unwrap();
+        keystore.save(&path).unwrap();
+
+        let loaded = EncryptedKeystore::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().find(Some("main")).unwrap().decrypt("secret").unwrap(), flagged_key);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn save_replaces_the_keystore_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = temp_path("replaced.json");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let mut keystore = empty_keystore();
+        keystore.add("main", &generate_ed25519_key(), "secret").unwrap();
+        keystore.save(&path).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        let loaded = EncryptedKeystore::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(loaded.unwrap().keys().len(), 1);
+    }
+
+    #[test]
+    fn read_new_passphrase_rejects_an_empty_file() {
+        let path = temp_path("passphrase");
+        fs::write(&path, "\n").unwrap();
+        let passphrase = read_new_passphrase(Some(&path));
+        fs::remove_file(&path).unwrap();
+        assert!(passphrase.is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::outcome::MoveAbortInfo;
+
+/// Every failure momentum-quant reports, so callers can react to each case
+/// without matching on messages.
+#[derive(Debug)]
+pub enum QuantError {
+    /// gRPC transport failure or error status returned by the node, boxed to
+    /// keep results small.
+    Rpc(Box<tonic::Status>),
+    /// The node has no object with this id.
+    ObjectNotFound(String),
+    /// An object is not owned the way its use requires.
+    WrongOwnership {
+        object_id: String,
+        expected: &'static str,
+    },
+    /// The owner's coins of `coin_type` hold less than `required` in total.
+    InsufficientBalance {
+        coin_type: String,
+        required: u64,
+        available: u64,
+        coins: usize,
+    },
+    /// The balance covers `required` but is spread over more coins than one
+    /// transaction can merge.
+    FragmentedBalance {
+        coin_type: String,
+        required: u64,
+        usable: u64,
+        coins: usize,
+    },
+    /// A Move call aborted, in command `command` if known.
+    MoveAbort {
+        abort: MoveAbortInfo,
+        command: Option<u64>,
+    },
+    /// The swap output failed its minimum output check, in command `command`.
+    SlippageExceeded {
+        command: Option<u64>,
+    },
+    /// The transaction failed other than by a Move abort.
+    ExecutionFailed {
+        command: Option<u64>,
+        message: String,
+    },
+    /// The transaction was not part of a checkpoint within `timeout`.
+    FinalityTimeout {
+        digest: String,
+        timeout: Duration,
+    },
+    /// The (simulated) output is below the accepted minimum.
+    OutputBelowMinimum {
+        coin_type: String,
+        received: i128,
+        min_amount_out: u64,
+    },
+    /// Loading, decoding or storing a private key failed.
+    Key(String),
+    /// Signing a transaction failed.
+    Signing(String),
+    /// The config file or network selection is invalid.
+    Config(String),
+    /// A node response lacks a field or holds an unexpected value.
+    InvalidResponse(String),
+    /// An argument is out of range or inconsistent with the others.
+    InvalidInput(String),
+    /// `value` is not a valid `what`.
+    Parse {
+        what: &'static str,
+        value: String,
+        reason: String,
+    },
+    /// The transaction builder rejected the transaction.
+    Build(String),
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl QuantError {
+    /// Map an RPC status to `ObjectNotFound` when it reports a missing object.
+    pub fn object_rpc(object_id: impl fmt::Display, status: tonic::Status) -> QuantError {
+        if status.code() == tonic::Code::NotFound {
+            QuantError::ObjectNotFound(object_id.to_string())
+        } else {
+            QuantError::Rpc(Box::new(status))
+        }
+    }
+
+    /// Whether the failure may come from an object input, e.g. a cached
+    /// version the node rejected.
+    pub fn is_input_object_error(&self) -> bool {
+        matches!(
+            self,
+            QuantError::Rpc(_)
+                | QuantError::ObjectNotFound(_)
+                | QuantError::WrongOwnership { .. }
+                | QuantError::ExecutionFailed { .. }
+        )
+    }
+
+    pub fn invalid_response(message: impl Into<String>) -> QuantError {
+        QuantError::InvalidResponse(message.into())
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> QuantError {
+        QuantError::InvalidInput(message.into())
+    }
+}
+
+/// Parse `value` as a `what`, e.g. `parse::<Address>(id, "object id")`.
+pub fn parse<T>(value: &str, what: &'static str) -> Result<T, QuantError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|e: T::Err| QuantError::Parse {
+        what,
+        value: value.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+impl fmt::Display for QuantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuantError::Rpc(status) => {
+                write!(f, "RPC error {:?}: {}", status.code(), status.message())
+            }
+            QuantError::ObjectNotFound(object_id) => write!(f, "Object {object_id} not found"),
+            QuantError::WrongOwnership { object_id, expected } => {
+                write!(f, "Object {object_id} is not {expected}")
+            }
+            QuantError::InsufficientBalance {
+                coin_type,
+                required,
+                available,
+                coins,
+            } => write!(
+                f,
+                "Insufficient balance of {coin_type}: need {required}, {available} available in {coins} coins"
+            ),
+            QuantError::FragmentedBalance {
+                coin_type,
+                required,
+                usable,
+                coins,
+            } => write!(
+                f,
+                "Balance of {coin_type} is too fragmented: {usable} of {required} in the largest {coins} coins, run consolidate first"
+            ),
+            QuantError::MoveAbort { abort, command } => {
+                write!(f, "Move abort {abort}")?;
+                if let Some(command) = command {
+                    write!(f, " (command {command})")?;
+                }
+                Ok(())
+            }
+            QuantError::SlippageExceeded { command } => {
+                write!(f, "Slippage exceeded: output below the minimum")?;
+                if let Some(command) = command {
+                    write!(f, " (command {command})")?;
+                }
+                Ok(())
+            }
+            QuantError::ExecutionFailed { command, message } => {
+                write!(f, "Transaction failed")?;
+                if let Some(command) = command {
+                    write!(f, " in command {command}")?;
+                }
+                write!(f, ": {message}")
+            }
+            QuantError::FinalityTimeout { digest, timeout } => {
+                write!(f, "Transaction {digest} not in a checkpoint after {timeout:?}")
+            }
+            QuantError::OutputBelowMinimum {
+                coin_type,
+                received,
+                min_amount_out,
+            } => write!(f, "Output {received} of {coin_type} is below {min_amount_out}"),
+            QuantError::Key(message)
+            | QuantError::Signing(message)
+            | QuantError::Config(message)
+            | QuantError::InvalidResponse(message)
+            | QuantError::InvalidInput(message)
+            | QuantError::Build(message) => write!(f, "{message}"),
+            QuantError::Parse { what, value, reason } => write!(f, "Invalid {what} '{value}': {reason}"),
+            QuantError::Io(e) => write!(f, "{e}"),
+            QuantError::Json(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for QuantError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            QuantError::Rpc(status) => Some(status.as_ref()),
+            QuantError::Io(e) => Some(e),
+            QuantError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<tonic::Status> for QuantError {
+    fn from(status: tonic::Status) -> QuantError {
+        QuantError::Rpc(Box::new(status))
+    }
+}
+
+impl From<io::Error> for QuantError {
+    fn from(e: io::Error) -> QuantError {
+        QuantError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for QuantError {
+    fn from(e: serde_json::Error) -> QuantError {
+        QuantError::Json(e)
+    }
+}
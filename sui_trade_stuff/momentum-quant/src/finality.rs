@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use prost_types::{FieldMask, Timestamp};
+use sui_rpc::Client;
+use sui_rpc::proto::sui::rpc::v2::GetTransactionRequest;
+use tokio::time::{Instant, sleep};
+
+use crate::error::QuantError;
+
+/// Enable / disable debug logs inside finality module.
+const DEBUG_FINALITY: bool = false;
+
+/// Delay between two `GetTransaction` polls.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Checkpoint a transaction was included in.
+#[derive(Clone, Copy, Debug)]
+pub struct Finality {
+    pub checkpoint: u64,
+    /// Checkpoint timestamp, in ms since the Unix epoch.
+    pub timestamp_ms: Option<u64>,
+}
+
+/// Poll `LedgerService.GetTransaction` until `digest` is part of a
+/// checkpoint, for at most `timeout`.
+///
+/// An executed transaction may not be readable from the node yet: not
+/// found is retried like a missing checkpoint.
+pub async fn wait_for_checkpoint(
+    client: &mut Client,
+    digest: &str,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<Finality, QuantError> {
+    let mut ledger_client = client.ledger_client();
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let mut request = GetTransactionRequest::default();
+        request.digest = Some(digest.to_string());
+        request.read_mask = Some(FieldMask {
+            paths: vec![
+            "checkpoint".to_string(),
+            "timestamp".to_string(),
+            ],
+        });
+
+        match ledger_client.get_transaction(request).await {
+            Ok(response) => {
+                let transaction = response.into_inner().transaction.unwrap_or_default();
+                if let Some(checkpoint) = transaction.checkpoint {
+                    let finality = Finality {
+                        checkpoint,
+                        timestamp_ms: transaction.timestamp.as_ref().and_then(timestamp_ms),
+                    };
+                    debug_finality(&format!("[wait_for_checkpoint] {digest}: {finality:?}"));
+                    return Ok(finality);
+                }
+                debug_finality(&format!("[wait_for_checkpoint] {digest}: no checkpoint yet"));
+            }
+            Err(status) if status.code() == tonic::Code::NotFound => {
+                debug_finality(&format!("[wait_for_checkpoint] {digest}: not found yet"));
+            }
+            Err(status) => return Err(status.into()),
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(QuantError::FinalityTimeout {
+                digest: digest.to_string(),
+                timeout,
+            });
+        }
+        sleep(poll_interval.min(deadline - now)).await;
+    }
+}
+
+/// Milliseconds since the Unix epoch, `None` before it.
+pub fn timestamp_ms(timestamp: &Timestamp) -> Option<u64> {
+    let seconds = u64::try_from(timestamp.seconds).ok()?;
+    let millis = u64::try_from(timestamp.nanos).ok()? / 1_000_000;
+    Some(seconds * 1000 + millis)
+}
+
+fn debug_finality(msg: &str) {
+    if DEBUG_FINALITY {
+        eprintln!("{msg}");
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use prost_types::FieldMask;
+use serde::{Deserialize, Serialize};
+use sui_rpc::Client;
+use sui_rpc::proto::sui::rpc::v2::GetEpochRequest;
+
+use crate::cache_file;
+use crate::error::QuantError;
+
+/// Enable / disable debug logs inside gas_price module.
+const DEBUG_GAS_PRICE: bool = false;
+
+/// Reference gas price cache file.
+const GAS_PRICE_CACHE_FILE: &str = "gas_price.json";
+
+/// Reference gas price of one epoch.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct EpochGasPrice {
+    pub epoch: u64,
+    pub reference_gas_price: u64,
+    /// Expected end of the epoch (start + epoch duration), in ms since the
+    /// Unix epoch; unknown ends are never trusted from the cache.
+    pub epoch_end_ms: Option<u64>,
+}
+
+impl EpochGasPrice {
+    /// Whether the epoch this price belongs to is still running.
+    pub fn is_current(&self) -> bool {
+        match (self.epoch_end_ms, now_ms()) {
+            (Some(end), Some(now)) => now < end,
+            _ => false,
+        }
+    }
+}
+
+/// Reference gas price of the current epoch of `network`, read from the
+/// cache file while that epoch lasts and fetched with `GetEpoch` otherwise.
+pub async fn reference_gas_price(
+    client: &mut Client,
+    network: &str,
+) -> Result<EpochGasPrice, QuantError> {
+    let path = cache_file::cache_path(GAS_PRICE_CACHE_FILE);
+    let mut cache: BTreeMap<String, EpochGasPrice> = cache_file::read(path.as_deref());
+
+    if let Some(cached) = cache.get(network).filter(|cached| cached.is_current()) {
+        debug_gas_price(&format!(
+            "[reference_gas_price] cached epoch={}, price={}",
+            cached.epoch, cached.reference_gas_price
+        ));
+        return Ok(*cached);
+    }
+
+    let fetched = fetch_epoch_gas_price(client).await?;
+    cache.insert(network.to_string(), fetched);
+    cache_file::write(path.as_deref(), &cache);
+    Ok(fetched)
+}
+
+/// Read the current epoch's reference gas price with `LedgerService.GetEpoch`.
+pub async fn fetch_epoch_gas_price(client: &mut Client) -> Result<EpochGasPrice, QuantError> {
+    let mut ledger_client = client.ledger_client();
+
+    // No epoch selects the current one.
+    let mut request = GetEpochRequest::default();
+    request.read_mask = Some(FieldMask {
+        paths: vec![
+        "epoch".to_string(),
+        "reference_gas_price".to_string(),
+        "start".to_string(),
+        "system_state.parameters.epoch_duration_ms".to_string(),
+        ],
+    });
+
+    let response = ledger_client.get_epoch(request).await?.into_inner();
+    let epoch = response
+        .epoch
+        .ok_or_else(|| QuantError::invalid_response("GetEpoch returned no epoch"))?;
+
+    let start_ms = epoch
+        .start
+        .as_ref()
+        .and_then(|start| u64::try_from(start.seconds).ok())
+        .map(|seconds| seconds * 1000);
+    let duration_ms = epoch
+        .system_state
+        .as_ref()
+        .and_then(|state| state.parameters.as_ref())
+        .and_then(|parameters| parameters.epoch_duration_ms);
+
+    let price = EpochGasPrice {
+        epoch: epoch
+            .epoch
+            .ok_or_else(|| QuantError::invalid_response("GetEpoch returned no epoch number"))?,
+        reference_gas_price: epoch
+            .reference_gas_price
+            .ok_or_else(|| QuantError::invalid_response("GetEpoch returned no reference gas price"))?,
+        epoch_end_ms: start_ms.zip(duration_ms).map(|(start, duration)| start + duration),
+    };
+    debug_gas_price(&format!("[fetch_epoch_gas_price] {price:?}"));
+    Ok(price)
+}
+
+/// Gas price bid: the reference price times `multiplier` (at least 1).
+pub fn priority_gas_price(reference_gas_price: u64, multiplier: f64) -> Result<u64, QuantError> {
+    if multiplier.is_nan() || multiplier < 1.0 {
+        return Err(QuantError::InvalidInput(format!(
+            "Gas price multiplier {multiplier} must be at least 1"
+        )));
+    }
+    let price = (reference_gas_price as f64 * multiplier).ceil();
+    if price >= u64::MAX as f64 {
+        return Err(QuantError::invalid_input("Gas price overflows u64"));
+    }
+    Ok(price as u64)
+}
+
+fn now_ms() -> Option<u64> {
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    u64::try_from(elapsed.as_millis()).ok()
+}
+
+fn debug_gas_price(msg: &str) {
+    if DEBUG_GAS_PRICE {
+        eprintln!("{msg}");
+    }
+}
@@ -0,0 +1,260 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use bech32::{FromBase32, ToBase32, Variant};
+use serde::Deserialize;
+use sui_sdk_types::Address;
+
+use crate::encrypted_keystore::{self, EncryptedKeystore};
+use crate::error::QuantError;
+use crate::signer::SuiKeyPair;
+
+/// Environment variable holding a bech32 `suiprivkey...` string.
+const ENV_PRIVATE_KEY: &str = "SUI_PRIVATE_KEY";
+
+/// Human readable part of bech32 encoded Sui private keys.
+const SUI_PRIVATE_KEY_HRP: &str = "suiprivkey";
+
+/// Keystore and alias files of the Sui CLI, relative to $HOME.
+const DEFAULT_KEYSTORE_PATH: &str = ".sui/sui_config/sui.keystore";
+const ALIASES_FILE: &str = "sui.aliases";
+
+/// Where to load the signing key from.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KeySource<'a> {
+    /// File containing a bech32 `suiprivkey...` string.
+    pub key_file: Option<&'a Path>,
+    /// Sui keystore file, defaults to `~/.sui/sui_config/sui.keystore`.
+    pub keystore: Option<&'a Path>,
+    /// Encrypted keystore, defaults to `~/.momentum-quant/keystore.json`.
+    pub encrypted_keystore: Option<&'a Path>,
+    /// File holding the encrypted keystore passphrase; prompted otherwise.
+    pub passphrase_file: Option<&'a Path>,
+    /// Alias or address of the key to use from the keystore.
+    pub select: Option<&'a str>,
+}
+
+/// Entry of the `sui.aliases` file written by the Sui CLI.
+#[derive(Deserialize, Debug)]
+struct AliasEntry {
+    alias: String,
+    public_key_base64: String,
+}
+
+/// Keys read from a Sui keystore file, with their aliases if known.
+pub struct Keystore {
+    path: PathBuf,
+    keys: Vec<SuiKeyPair>,
+    aliases: Vec<AliasEntry>,
+}
+
+/// Load the signing key.
+///
+/// `key_file` wins, then an explicitly passed keystore or `--key` selector,
+/// then `$SUI_PRIVATE_KEY`, then the encrypted keystore (if given or present
+/// at its default path), then the plain Sui keystore.
+pub fn load_private_key(source: KeySource<'_>) -> Result<SuiKeyPair, QuantError> {
+    if let Some(path) = source.key_file {
+        let content = fs::read_to_string(path)
+            .map_err(|e| {
+                QuantError::Key(format!("Failed to read key file {}: {e}", path.display()))
+            })?;
+        return decode_sui_private_key(content.trim());
+    }
+
+    // The environment only applies when no keystore was asked for on the
+    // command line.
+    let explicit_keystore =
+        source.keystore.is_some() || source.encrypted_keystore.is_some() || source.select.is_some();
+    if let Some(key) = env::var(ENV_PRIVATE_KEY).ok().filter(|_| !explicit_keystore) {
+        return decode_sui_private_key(key.trim())
+            .map_err(|e| QuantError::Key(format!("Invalid {ENV_PRIVATE_KEY}: {e}")));
+    }
+
+    if source.keystore.is_none() {
+        let encrypted_path = source
+            .encrypted_keystore
+            .map(Path::to_path_buf)
+            .or_else(encrypted_keystore::default_encrypted_keystore_path)
+            .filter(|path| source.encrypted_keystore.is_some() || path.exists());
+
+        if let Some(path) = encrypted_path {
+            let keystore = EncryptedKeystore::load(&path)?;
+            let key = keystore.find(source.select)?;
+            let passphrase = encrypted_keystore::read_passphrase(
+                source.passphrase_file,
+                &format!("Passphrase for {} ({}): ", key.alias, key.address),
+            )?;
+            return key.unlock(&passphrase);
+        }
+    }
+
+    let path = match source.keystore {
+        Some(path) => path.to_path_buf(),
+        None => default_keystore_path().ok_or_else(|| {
+            QuantError::Key(
+                "No private key found: set SUI_PRIVATE_KEY, pass --key-file, --encrypted-keystore or --keystore"
+                    .to_string(),
+            )
+        })?,
+    };
+
+    Keystore::load(&path)?.select(source.select)
+}
+
+/// Read the `flag || key` bytes of a plaintext key to import, from
+/// `key_file`, `$SUI_PRIVATE_KEY` or a hidden terminal prompt.
+pub fn read_private_key_bytes(source: KeySource<'_>) -> Result<Vec<u8>, QuantError> {
+    let key = if let Some(path) = source.key_file {
+        fs::read_to_string(path)
+            .map_err(|e| {
+                QuantError::Key(format!("Failed to read key file {}: {e}", path.display()))
+            })?
+    } else if let Ok(key) = env::var(ENV_PRIVATE_KEY) {
+        key
+    } else {
+        rpassword::prompt_password("Private key (suiprivkey...): ")?
+    };
+
+    let bytes = decode_sui_private_key_bytes(key.trim())?;
+    // Validate the scheme and key before storing it.
+    SuiKeyPair::from_flagged_bytes(&bytes)?;
+    Ok(bytes)
+}
+
+/// Decode a Sui private key of any scheme from bech32 "suiprivkey..." string.
+pub fn decode_sui_private_key(key_str: &str) -> Result<SuiKeyPair, QuantError> {
+    SuiKeyPair::from_flagged_bytes(&decode_sui_private_key_bytes(key_str)?)
+}
+
+/// Decode a bech32 "suiprivkey..." string into its `flag || key` bytes.
+pub fn decode_sui_private_key_bytes(key_str: &str) -> Result<Vec<u8>, QuantError> {
+    if key_str.is_empty() {
+        return Err(QuantError::Key("Sui private key is empty".to_string()));
+    }
+
+    let (hrp, data, _variant) = bech32::decode(key_str)
+        .map_err(|e| QuantError::Key(format!("Invalid Sui private key: {e}")))?;
+    if hrp != SUI_PRIVATE_KEY_HRP {
+        return Err(QuantError::Key(format!(
+            "Invalid Sui private key prefix '{hrp}', expected '{SUI_PRIVATE_KEY_HRP}'"
+        )));
+    }
+    Vec::<u8>::from_base32(&data)
+        .map_err(|e| QuantError::Key(format!("Invalid Sui private key: {e}")))
+}
+
+/// Encode `flag || key` bytes as a bech32 "suiprivkey..." string.
+pub fn encode_sui_private_key(flagged_key: &[u8]) -> Result<String, QuantError> {
+    bech32::encode(
+        SUI_PRIVATE_KEY_HRP,
+        flagged_key.to_base32(),
+        Variant::Bech32,
+    )
+    .map_err(|e| QuantError::Key(format!("Failed to encode Sui private key: {e}")))
+}
+
+impl Keystore {
+    /// Read a `sui.keystore` file (a JSON array of base64 `flag || key`
+    /// entries) and the `sui.aliases` file next to it, if present.
+    pub fn load(path: &Path) -> Result<Keystore, QuantError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| {
+                QuantError::Key(format!("Failed to read keystore {}: {e}", path.display()))
+            })?;
+        let entries: Vec<String> = serde_json::from_str(&content)
+            .map_err(|e| QuantError::Key(format!("Malformed keystore {}: {e}", path.display())))?;
+
+        let keys = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| -> Result<SuiKeyPair, QuantError> {
+                let bytes = BASE64
+                    .decode(entry.trim())
+                    .map_err(|e| QuantError::Key(format!("Malformed keystore entry {i}: {e}")))?;
+                SuiKeyPair::from_flagged_bytes(&bytes)
+                    .map_err(|e| QuantError::Key(format!("Malformed keystore entry {i}: {e}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let aliases_path = path.with_file_name(ALIASES_FILE);
+        let aliases = match fs::read_to_string(&aliases_path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| {
+                    QuantError::Key(format!("Malformed aliases {}: {e}", aliases_path.display()))
+                })?,
+            Err(_) => Vec::new(),
+        };
+
+        Ok(Keystore {
+            path: path.to_path_buf(),
+            keys,
+            aliases,
+        })
+    }
+
+    /// Pick a key by alias or address. Without a selector the keystore must
+    /// hold exactly one key.
+    pub fn select(self, select: Option<&str>) -> Result<SuiKeyPair, QuantError> {
+        let Some(select) = select else {
+            return match self.keys.len() {
+                0 => Err(QuantError::Key(format!("Keystore {} is empty", self.path.display()))),
+                1 => Ok(self.keys.into_iter().next().unwrap()),
+                n => Err(QuantError::Key(format!(
+                    "Keystore {} holds {n} keys, select one with --key <alias|address>: {}",
+                    self.path.display(),
+                    self.addresses().join(", "),
+                ))),
+            };
+        };
+
+        let address: Address = match select.parse() {
+            Ok(address) => address,
+            Err(_) => self.alias_address(select)?,
+        };
+
+        let path = self.path;
+        self.keys
+            .into_iter()
+            .find(|key| key.derive_address() == address)
+            .ok_or_else(|| {
+                QuantError::Key(format!("Key {select} not found in keystore {}", path.display()))
+            })
+    }
+
+    fn alias_address(&self, alias: &str) -> Result<Address, QuantError> {
+        let entry = self
+            .aliases
+            .iter()
+            .find(|entry| entry.alias == alias)
+            .ok_or_else(|| QuantError::Key(format!("Unknown key alias '{alias}'")))?;
+
+        let bytes = BASE64
+            .decode(&entry.public_key_base64)
+            .map_err(|e| {
+                QuantError::Key(format!("Malformed public key for alias '{alias}': {e}"))
+            })?;
+
+        self.keys
+            .iter()
+            .find(|key| key.public_key_bytes() == bytes)
+            .map(|key| key.derive_address())
+            .ok_or_else(|| QuantError::Key(format!("Alias '{alias}' has no key in keystore")))
+    }
+
+    fn addresses(&self) -> Vec<String> {
+        self.keys
+            .iter()
+            .map(|key| format!("{} ({})", key.derive_address(), key.scheme()))
+            .collect()
+    }
+}
+
+fn default_keystore_path() -> Option<PathBuf> {
+    let home = env::var_os("HOME")?;
+    let path = PathBuf::from(home).join(DEFAULT_KEYSTORE_PATH);
+    path.exists().then_some(path)
+}
@@ -1,243 +1,1265 @@
-use std::error::Error;
+use std::collections::BTreeMap;
+use std::time::Duration;
 
-use bech32::FromBase32;
-use sui_crypto::ed25519::Ed25519PrivateKey;
-use sui_crypto::SuiSigner;
+use clap::Parser;
 use sui_rpc::Client;
-use sui_rpc::proto::sui::rpc::v2::{ListOwnedObjectsRequest, GetObjectRequest};
-use sui_sdk_types::{Address, Digest};
+use sui_rpc::proto::sui::rpc::v2::{
+    ExecuteTransactionRequest, ExecuteTransactionResponse, GetServiceInfoRequest,
+};
+use sui_sdk_types::{Address, Transaction, TypeTag};
 use sui_transaction_builder::unresolved::Input;
 use prost_types::FieldMask;
 use sui_rpc::proto::sui::rpc::v2::Object;
+use sui_rpc::proto::sui::rpc::v2::owner::OwnerKind;
 use tokio::time::Instant;
+
+use zeroize::Zeroize;
+
+use cli::{
+    Cli, Command, ConsolidateArgs, KeysArgs, KeysCommand, QuoteArgs, ReportFormat, RouteArgs, SwapArgs,
+};
+use config::{Config, NetworkConfig};
+use encrypted_keystore::EncryptedKeystore;
+use error::QuantError;
+use keystore::KeySource;
+use momentum::{CoinSource, RouteHop, SwapAmount, SwapPool};
+use object_cache::{CachedObject, ObjectCache};
+use outcome::{AbortContext, ExecutionOutcome};
+use report::TradeReport;
+use quote::PoolState;
+use router::{GraphPool, PoolGraph};
+use signer::SuiKeyPair;
+use slippage::{Slippage, SlippageLimits};
+/// `println!` for progress and status lines; they go to stderr while stdout
+/// carries a JSON trade report.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if $crate::report::status_on_stderr() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+mod cache_file;
+mod cli;
+mod coins;
+mod config;
+mod encrypted_keystore;
+mod error;
+mod finality;
+mod gas_price;
+mod keystore;
 mod momentum;
+mod object_cache;
+mod objects;
+mod outcome;
+mod quote;
+mod report;
+mod router;
+mod signer;
+mod simulate;
+mod slippage;
 
 /// Enable / disable debug logs in main.rs.
 const DEBUG_MAIN: bool = true;
 
-/// Default swap amount (in smallest unit of the token).
-const DEFAULT_SWAP_AMOUNT: u64 = 1_000_000;
+/// How long each of several RPC endpoints gets to answer before the next
+/// one is tried.
+const RPC_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
 
-/// Default gas budget and gas price.
-const DEFAULT_GAS_BUDGET: u64 = 500_000_00;
-const DEFAULT_GAS_PRICE: u64 = 1_000;
+/// Coin type filter matching every `Coin<T>` owned by an address.
+const COIN_TYPE_FILTER: &str = "0x2::coin::Coin";
 
-/// Hard-coded pool object id and token object id used in the example.
-const DEFAULT_POOL_ID: &str =
-    "0x455cf8d2ac91e7cb883f515874af750ed3cd18195c970b7a2d46235ac2b0c388";
-const DEFAULT_TOKEN_OBJECT_ID: &str =
-    "0x024ebdcd5cfee93cf032dd2091fb0a8e570734595577cb10ec6df46e5c11432c";
-
-/// Example private key (bech32 suiprivkey format).
-const EXAMPLE_PRIVATE_KEY: &str =
-    "";
-
-const VERSIONED_OBJECT_ID: &str =
-    "0x2375a0b1ec12010aaea3b2545acfa2ad34cfbba03ce4b59f4c39e1e25eed1b2a";
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> Result<(), QuantError> {
     debug_main("[main] start");
+    let cli = Cli::parse();
+    if matches!(&cli.command, Command::Swap(args) if args.report == ReportFormat::Json) {
+        report::reserve_stdout();
+    }
+
+    let key_source = KeySource {
+        key_file: cli.key_file.as_deref(),
+        keystore: cli.keystore.as_deref(),
+        encrypted_keystore: cli.encrypted_keystore.as_deref(),
+        passphrase_file: cli.passphrase_file.as_deref(),
+        select: cli.key.as_deref(),
+    };
+
+    // Key management does not touch the network.
+    if let Command::Keys(args) = cli.command {
+        return run_keys(key_source, args);
+    }
+
+    let network = Config::load_network(cli.config.as_deref(), &cli.network)?;
+
+    // Create Sui gRPC client.
+    let rpc_urls = match &cli.rpc_url {
+        Some(rpc_url) => vec![rpc_url.clone()],
+        None => network.rpc_urls.clone(),
+    };
+    let (mut client, rpc_url) = connect(&rpc_urls).await?;
+    status!("Sui gRPC client connected");
+    debug_main(&format!("[main] Sui gRPC client created: {rpc_url} ({})", cli.network));
+
+    match cli.command {
+        Command::Swap(args) => {
+            let mut cache = ObjectCache::load(&cli.network);
+            let result = run_swap(&mut client, &network, &mut cache, key_source, args).await;
+            // Executed swaps already moved the cache to their effects; only
+            // a rejected input says a cached version may be wrong.
+            if result.as_ref().is_err_and(QuantError::is_input_object_error) {
+                cache.clear_owned();
+                evict_stale_shared(&mut client, &mut cache).await;
+            }
+            cache.save();
+            result
+        }
+        Command::Quote(args) => run_quote(&mut client, &network, args).await,
+        Command::Route(args) => run_route(&mut client, &network, args).await,
+        Command::Balances => run_balances(&mut client, key_source).await,
+        Command::Gas => run_gas(&mut client, key_source).await,
+        Command::Consolidate(args) => {
+            let mut cache = ObjectCache::load(&cli.network);
+            let result = run_consolidate(&mut client, &network, &mut cache, key_source, args).await;
+            if result.as_ref().is_err_and(QuantError::is_input_object_error) {
+                cache.clear_owned();
+            }
+            cache.save();
+            result
+        }
+        Command::Keys(_) => unreachable!("handled before connecting"),
+    }
+}
+
+/// Client of the first of `rpc_urls` answering `GetServiceInfo`. A single
+/// URL is used without the probe.
+async fn connect(rpc_urls: &[String]) -> Result<(Client, &str), QuantError> {
+    if let [rpc_url] = rpc_urls {
+        return Ok((Client::new(rpc_url)?, rpc_url));
+    }
+
+    let mut last_error = QuantError::Config("No rpc_urls configured".to_string());
+    for rpc_url in rpc_urls {
+        match probe_rpc(rpc_url).await {
+            Ok(client) => return Ok((client, rpc_url)),
+            Err(e) => {
+                status!("RPC endpoint {rpc_url} unavailable: {e}");
+                last_error = e;
+            }
+        }
+    }
+    Err(last_error)
+}
+
+async fn probe_rpc(rpc_url: &str) -> Result<Client, QuantError> {
+    let mut client = Client::new(rpc_url)?;
+    {
+        let mut ledger_client = client.ledger_client();
+        let request = ledger_client.get_service_info(GetServiceInfoRequest::default());
+        tokio::time::timeout(RPC_PROBE_TIMEOUT, request)
+            .await
+            .map_err(|_| tonic::Status::deadline_exceeded(format!("No answer within {RPC_PROBE_TIMEOUT:?}")))??;
+    }
+    Ok(client)
+}
+
+/// Load the signing key and derive the owner address.
+fn load_signer(key_source: KeySource<'_>) -> Result<(SuiKeyPair, Address), QuantError> {
+    let private_key = keystore::load_private_key(key_source)?;
+    let owner_address = private_key.derive_address();
+
+    status!("Owner address: {:?}", owner_address);
+    debug_main(&format!(
+        "[main] decoded {} private key and derived address",
+        private_key.scheme()
+    ));
+    Ok((private_key, owner_address))
+}
+
+async fn run_swap(
+    client: &mut Client,
+    network: &NetworkConfig,
+    cache: &mut ObjectCache,
+    key_source: KeySource<'_>,
+    mut args: SwapArgs,
+) -> Result<(), QuantError> {
+    if args.split {
+        let output_type = args
+            .to
+            .take()
+            .ok_or_else(|| QuantError::invalid_input("--split requires --to"))?;
+        return run_split_swap(client, network, cache, key_source, args, &output_type).await;
+    }
+    if let Some(output_type) = args.to.take() {
+        let (route, graph) = find_best_route(client, network, &args, &output_type).await?;
+        args.route = route;
+        return run_route_swap(client, network, cache, key_source, args, Some(&graph)).await;
+    }
+    if !args.route.is_empty() {
+        return run_route_swap(client, network, cache, key_source, args, None).await;
+    }
+
     let start = Instant::now();
-    // 1. Decode private key from bech32 "suiprivkey..." format.
-    let private_key = decode_sui_private_key(EXAMPLE_PRIVATE_KEY)?;
-    let public_key = private_key.public_key();
-    let owner_address = public_key.derive_address();
-
-    println!("Owner address: {:?}", owner_address);
-    debug_main("[main] decoded private key and derived address");
-
-    // 2. Create Sui gRPC client (testnet).
-    let mut client = Client::new("http://3.114.103.176:443")?;
-    println!("Sui gRPC client connected");
-    debug_main("[main] Sui gRPC client created");
-
-    // 3. Query owned SUI coins to get a gas object id (only object id, no version/digest).
-    let gas_object_id = fetch_first_sui_gas_object_id(&mut client, &owner_address).await?;
-    println!("Selected gas object id: {:?}", gas_object_id);
+    // 1. Load private key from file, env or keystore.
+    let (private_key, owner_address) = load_signer(key_source)?;
+
+    // 2. Prepare swap parameters.
+    let pool_object_id = parse_pool_id(network, &args.pool)?;
+    let versioned_object_id: Address =
+        error::parse(&network.packages.versioned_object, "versioned object id")?;
+    let clock_object_id: Address = error::parse("0x6", "object id")?; // Sui system clock object id
+    let token_ids = token_object_ids(&args)?;
+
+    // Fetch the pool and every uncached input in one batch.
+    let pool_obj = load_swap_objects(
+        client,
+        cache,
+        &[pool_object_id],
+        &[clock_object_id, versioned_object_id],
+        &token_ids,
+    )
+    .await?
+    .remove(0);
+
+    let swap_pool = resolve_swap_pool(network, &args.pool, &pool_obj)?;
     debug_main(&format!(
-        "[main] fetched gas object id: {gas_object_id}"
+        "[main] pool coin types: A={}, B={}",
+        swap_pool.coin_type_a, swap_pool.coin_type_b
     ));
-    // 4. Prepare swap parameters.
-    let pool_object_id: Address = DEFAULT_POOL_ID.parse()?;
-    let token_object_id: Address = DEFAULT_TOKEN_OBJECT_ID.parse()?;
-    let versioned_object_id: Address = VERSIONED_OBJECT_ID.parse()?;
-    let clock_object_id: Address = "0x6".parse()?; // Sui system clock object id
-    
-    // Fetch object details
-    let gas_obj = fetch_object_details(&mut client, gas_object_id).await?;
-    let pool_obj = fetch_object_details(&mut client, pool_object_id).await?;
-    let token_obj = fetch_object_details(&mut client, token_object_id).await?;
-    let version_obj = fetch_object_details(&mut client, versioned_object_id).await?;
-    let clock_obj = fetch_object_details(&mut client, clock_object_id).await?;
-
-    // Construct Inputs
-    // Token (Owned)
-    let token_version = token_obj.version.ok_or("Missing version for token")?;
-    let token_digest_str = token_obj.digest.ok_or("Missing digest for token")?;
-    let token_digest: Digest = token_digest_str.parse()?;
-
-    let gas_version = gas_obj.version.ok_or("Missing version for gas object")?;
-    let gas_digest_str = gas_obj.digest.ok_or("Missing digest for gas object")?;
-    let gas_digest: Digest = gas_digest_str.parse()?;
-
-    let gas_input = Input::by_id(gas_object_id)
-        .with_owned_kind()
-        .with_version(gas_version)
-        .with_digest(gas_digest);
-
-    let token_input = Input::by_id(token_object_id)
-        .with_owned_kind()
-        .with_version(token_version)
-        .with_digest(token_digest);
 
     // Pool (Shared)
     let initial_shared_version = get_initail_shared_version(&pool_obj)?;
-    let clock_version = get_initail_shared_version(&clock_obj)?;
-    let version_version = get_initail_shared_version(&version_obj)?;
-    println!("Initial shared versions - pool: {}, clock: {}, versioned: {}", initial_shared_version, clock_version, version_version);
+    status!("Initial shared version - pool: {}", initial_shared_version);
     let pool_input = Input::by_id(pool_object_id)
         .with_shared_kind()
         .with_initial_shared_version(initial_shared_version)
         .by_val();
 
-    let clock_input = Input::by_id(clock_object_id)
-        .with_shared_kind()
-        .with_initial_shared_version(clock_version)
-        .by_ref();
-
-    let version_input = Input::by_id(versioned_object_id)
-        .with_shared_kind()
-        .with_initial_shared_version(version_version)
-        .by_val();
+    // Clock and versioned object (Shared), cached by load_swap_objects.
+    let clock_input = cached_shared_input(client, cache, clock_object_id).await?.by_ref();
+    let version_input = cached_shared_input(client, cache, versioned_object_id).await?.by_val();
 
-    let amount: u64 = DEFAULT_SWAP_AMOUNT;
-    let direction: bool = true; // true: A -> B, false: B -> A
+    let amount: u64 = args.amount;
+    let direction: bool = args.direction.a_to_b(); // true: A -> B, false: B -> A
 
     debug_main(&format!(
-        "[main] swap params: token={token_object_id}, pool={pool_object_id}, amount={amount}, direction={direction}"
+        "[main] swap params: pool={pool_object_id}, amount={amount}, direction={direction}"
     ));
 
-    // 5. Build transaction (all inputs created via by_id).
+    // Slippage limits from the pool's current state; only a tolerance needs
+    // the tick table to quote the swap.
+    let slippage = match (args.min_out, args.max_in) {
+        (Some(min_out), _) => Slippage::MinAmountOut(min_out),
+        (_, Some(max_in)) => Slippage::MaxAmountIn(max_in),
+        _ => Slippage::ToleranceBps(args.slippage_bps),
+    };
+    let pool_state = match slippage {
+        Slippage::ToleranceBps(_) => PoolState::load(client, pool_json(&pool_obj)?).await?,
+        _ => PoolState::from_json(pool_json(&pool_obj)?)?,
+    };
+    let (swap_amount, limits) = if args.exact_out {
+        let limits = SlippageLimits::exact_out(slippage, &pool_state, amount, direction)?;
+        let swap_amount = SwapAmount::ExactOut {
+            amount_out: amount,
+            max_amount_in: limits.max_amount_in,
+        };
+        (swap_amount, limits)
+    } else {
+        let limits = SlippageLimits::new(slippage, &pool_state, amount, direction)?;
+        (SwapAmount::ExactIn(amount), limits)
+    };
+    status!(
+        "Slippage limits: sqrt_price_limit={}, min_amount_out={}, max_amount_in={}",
+        limits.sqrt_price_limit, limits.min_amount_out, limits.max_amount_in
+    );
+
+    // 3. Input coin and SUI gas coins covering the budget, never the swap input.
+    let input_type = if direction { &swap_pool.coin_type_a } else { &swap_pool.coin_type_b };
+    let (token, gas_inputs) =
+        prepare_input(client, cache, &owner_address, &args, input_type, swap_amount.max_amount_in()).await?;
+
+    // Gas price from the current epoch unless fixed.
+    let gas_price = resolve_gas_price(client, network, args.gas_price, args.gas_price_multiplier).await?;
+
+    // 4. Build transaction (all inputs created via by_id).
     debug_main("[main] before create_swap_transaction");
     let tx = momentum::create_swap_transaction(
-        token_input,
+        token,
         pool_input,
-        gas_input,
-        amount,
+        gas_inputs,
+        swap_amount,
         direction,
         owner_address,
-        DEFAULT_GAS_BUDGET,
-        DEFAULT_GAS_PRICE,
+        args.initial_gas_budget(),
+        gas_price,
         clock_input,
         version_input,
+        &swap_pool,
+        &limits,
     )?;
     debug_main("[main] after create_swap_transaction (tx built)");
 
-    // 6. Sign transaction.
+    // 5-6. Simulate if requested, then sign and execute transaction.
+    let output_type = if direction { &swap_pool.coin_type_b } else { &swap_pool.coin_type_a };
+    submit_swap(client, network, cache, &private_key, tx, (input_type, output_type), &args, start).await
+}
+
+/// Swap along `--route`, one flash swap per pool in a single transaction.
+///
+/// The pools of a route found in `graph` are not read again.
+async fn run_route_swap(
+    client: &mut Client,
+    network: &NetworkConfig,
+    cache: &mut ObjectCache,
+    key_source: KeySource<'_>,
+    args: SwapArgs,
+    graph: Option<&PoolGraph>,
+) -> Result<(), QuantError> {
+    let start = Instant::now();
+    let (private_key, owner_address) = load_signer(key_source)?;
+
+    let versioned_object_id: Address =
+        error::parse(&network.packages.versioned_object, "versioned object id")?;
+    let clock_object_id: Address = error::parse("0x6", "object id")?; // Sui system clock object id
+
+    // Pools of the route, in order.
+    let mut pool_ids = Vec::with_capacity(args.route.len());
+    for pool in &args.route {
+        let pool_object_id = parse_pool_id(network, pool)?;
+        if pool_ids.contains(&pool_object_id) {
+            return Err(QuantError::InvalidInput(format!("Pool '{pool}' appears twice in the route")));
+        }
+        pool_ids.push(pool_object_id);
+    }
+    let graph_pools = graph.map(|graph| graph_pools(graph, &pool_ids)).transpose()?;
+    let fetched_ids: &[Address] = match graph_pools {
+        Some(_) => &[],
+        None => &pool_ids,
+    };
+    let fetched_pools = load_swap_objects(
+        client,
+        cache,
+        fetched_ids,
+        &[clock_object_id, versioned_object_id],
+        &token_object_ids(&args)?,
+    )
+    .await?;
+    let pool_objs: Vec<&Object> = match &graph_pools {
+        Some(pools) => pools.iter().map(|pool| &pool.object).collect(),
+        None => fetched_pools.iter().collect(),
+    };
+    let version_input = cached_shared_input(client, cache, versioned_object_id).await?;
+    let clock_input = cached_shared_input(client, cache, clock_object_id).await?;
+
+    let mut pool_inputs = Vec::with_capacity(args.route.len());
+    let mut swap_pools = Vec::with_capacity(args.route.len());
+    for ((pool, pool_object_id), pool_obj) in args.route.iter().zip(&pool_ids).zip(&pool_objs) {
+        swap_pools.push(resolve_swap_pool(network, pool, pool_obj)?);
+        pool_inputs.push(shared_input(*pool_object_id, pool_obj)?.by_val());
+    }
+
+    let input_type = swap_input_type(client, &args).await?;
+    let hops: Vec<RouteHop> = momentum::plan_route(swap_pools, &input_type)?;
+    let (token, gas_inputs) = prepare_input(client, cache, &owner_address, &args, &input_type, args.amount).await?;
+    for (i, hop) in hops.iter().enumerate() {
+        status!("Hop {}: {} -> {} ({})", i + 1, hop.input_type(), hop.output_type(), args.route[i]);
+    }
+
+    let slippage = match args.min_out {
+        Some(min_out) => Slippage::MinAmountOut(min_out),
+        None => Slippage::ToleranceBps(args.slippage_bps),
+    };
+    // Only a tolerance needs the tick tables to quote the route.
+    let mut pool_states = Vec::with_capacity(pool_objs.len());
+    match &graph_pools {
+        Some(pools) => pool_states.extend(pools.iter().map(|pool| pool.state.clone())),
+        None => {
+            for pool_obj in &pool_objs {
+                pool_states.push(match slippage {
+                    Slippage::ToleranceBps(_) => PoolState::load(client, pool_json(pool_obj)?).await?,
+                    _ => PoolState::from_json(pool_json(pool_obj)?)?,
+                });
+            }
+        }
+    }
+    let hop_states: Vec<(&PoolState, bool)> = pool_states
+        .iter()
+        .zip(&hops)
+        .map(|(state, hop)| (state, hop.a_to_b))
+        .collect();
+    let min_amount_out = slippage::route_min_amount_out(slippage, &hop_states, args.amount)?;
+    status!("Route minimum output: {min_amount_out}");
+
+    let gas_price = resolve_gas_price(client, network, args.gas_price, args.gas_price_multiplier).await?;
+    let tx = momentum::create_route_transaction(
+        token,
+        pool_inputs,
+        gas_inputs,
+        args.amount,
+        owner_address,
+        args.initial_gas_budget(),
+        gas_price,
+        clock_input.by_ref(),
+        version_input.by_val(),
+        &hops,
+        min_amount_out,
+    )?;
+
+    let output_type = hops
+        .last()
+        .ok_or_else(|| QuantError::invalid_input("Empty route"))?
+        .output_type();
+    submit_swap(client, network, cache, &private_key, tx, (&input_type, output_type), &args, start).await
+}
+
+/// Sell `--amount` through every configured pool of the pair at once,
+/// split by the local optimizer.
+async fn run_split_swap(
+    client: &mut Client,
+    network: &NetworkConfig,
+    cache: &mut ObjectCache,
+    key_source: KeySource<'_>,
+    args: SwapArgs,
+    output_type: &str,
+) -> Result<(), QuantError> {
+    let start = Instant::now();
+    let (private_key, owner_address) = load_signer(key_source)?;
+
+    let versioned_object_id: Address =
+        error::parse(&network.packages.versioned_object, "versioned object id")?;
+    let clock_object_id: Address = error::parse("0x6", "object id")?; // Sui system clock object id
+
+    let input_type = swap_input_type(client, &args).await?;
+    let graph = PoolGraph::load(client, network).await?;
+    let output_type: TypeTag = error::parse(output_type, "coin type")?;
+    let plan = graph.split_order(&input_type, &output_type, args.amount)?;
+
+    // Pools of the plan come from the graph; only uncached inputs are read.
+    let pools = graph_pools(&graph, &plan.pool_ids)?;
+    load_swap_objects(
+        client,
+        cache,
+        &[],
+        &[clock_object_id, versioned_object_id],
+        &token_object_ids(&args)?,
+    )
+    .await?;
+    let version_input = cached_shared_input(client, cache, versioned_object_id).await?;
+    let clock_input = cached_shared_input(client, cache, clock_object_id).await?;
+    let (token, gas_inputs) = prepare_input(client, cache, &owner_address, &args, &input_type, args.amount).await?;
+    status!("Split order, expected out {}:", plan.amount_out);
+
+    // Per-leg price limits, quoted against the graph's pool states; the
+    // total minimum is the sum of the leg minimums unless given explicitly.
+    let mut pool_inputs = Vec::with_capacity(plan.legs.len());
+    let mut limits = Vec::with_capacity(plan.legs.len());
+    for (i, (leg, pool)) in plan.legs.iter().zip(&pools).enumerate() {
+        let leg_limits = match args.min_out {
+            Some(_) => SlippageLimits::unbounded(leg.hop.a_to_b),
+            None => SlippageLimits::new(
+                Slippage::ToleranceBps(args.slippage_bps),
+                &pool.state,
+                leg.amount_in,
+                leg.hop.a_to_b,
+            )?,
+        };
+        status!(
+            "  {}: in {} -> out ~{} (min {})",
+            plan.pools[i], leg.amount_in, plan.leg_amounts_out[i], leg_limits.min_amount_out
+        );
+        pool_inputs.push(shared_input(pool.pool_id, &pool.object)?.by_val());
+        limits.push(leg_limits);
+    }
+    let min_amount_out = args
+        .min_out
+        .unwrap_or_else(|| limits.iter().map(|limits| limits.min_amount_out).sum());
+
+    let gas_price = resolve_gas_price(client, network, args.gas_price, args.gas_price_multiplier).await?;
+    let tx = momentum::create_split_transaction(
+        token,
+        pool_inputs,
+        gas_inputs,
+        owner_address,
+        args.initial_gas_budget(),
+        gas_price,
+        clock_input.by_ref(),
+        version_input.by_val(),
+        &plan.legs,
+        &limits,
+        min_amount_out,
+    )?;
+
+    submit_swap(client, network, cache, &private_key, tx, (&input_type, &output_type), &args, start).await
+}
+
+/// Pool ids of the best route from the input coin to `output_type`, with
+/// the graph it was found in.
+async fn find_best_route(
+    client: &mut Client,
+    network: &NetworkConfig,
+    args: &SwapArgs,
+    output_type: &str,
+) -> Result<(Vec<String>, PoolGraph), QuantError> {
+    let input_type = swap_input_type(client, args).await?;
+
+    let graph = PoolGraph::load(client, network).await?;
+    let output_type = error::parse(output_type, "coin type")?;
+    let best = graph.best_route(&input_type, &output_type, args.amount, args.max_hops)?;
+    status!(
+        "Best route: {} (expected out {}, est. gas {} MIST)",
+        best.pools.join(" -> "),
+        best.amount_out,
+        best.gas_cost
+    );
+    if best.gas_cost_in_output.is_none() {
+        status!("Gas cannot be priced in {output_type} through the pools, routes ranked by output");
+    }
+    Ok((best.pool_ids.iter().map(|id| id.to_string()).collect(), graph))
+}
+
+/// Graph pools of `pool_ids`, in order.
+fn graph_pools<'a>(graph: &'a PoolGraph, pool_ids: &[Address]) -> Result<Vec<&'a GraphPool>, QuantError> {
+    pool_ids
+        .iter()
+        .map(|pool_id| {
+            graph
+                .pool(pool_id)
+                .ok_or_else(|| QuantError::InvalidInput(format!("Pool {pool_id} outside the pool graph")))
+        })
+        .collect()
+}
+
+/// Input coin type of a route or split order: `--from`, else the type of
+/// the `--token` coin.
+async fn swap_input_type(client: &mut Client, args: &SwapArgs) -> Result<TypeTag, QuantError> {
+    if let Some(from) = &args.from {
+        return error::parse(from, "coin type");
+    }
+    let token = args
+        .token
+        .as_deref()
+        .ok_or_else(|| {
+            QuantError::invalid_input("--route and --to need --from or --token for the input coin type")
+        })?;
+    let token_obj =
+        objects::fetch_object(client, error::parse(token, "object id")?, objects::OBJECT_FIELDS).await?;
+    momentum::coin_object_type(
+        token_obj
+            .object_type
+            .as_deref()
+            .ok_or_else(|| QuantError::invalid_response("Missing object_type for token"))?,
+    )
+}
+
+/// Coins the swap input is split from, and a gas payment for them.
+///
+/// SUI inputs are split from the gas coin, so the gas coins must cover the
+/// budget plus `amount_in`. Other inputs come from `--token`, read through
+/// `cache`, or from as many of the owner's coins of `input_type` as it takes
+/// to cover `amount_in`. Gas and input coins come from `cache` once listed.
+async fn prepare_input(
+    client: &mut Client,
+    cache: &mut ObjectCache,
+    owner: &Address,
+    args: &SwapArgs,
+    input_type: &TypeTag,
+    amount_in: u64,
+) -> Result<(CoinSource, Vec<Input>), QuantError> {
+    if momentum::is_sui(input_type) {
+        status!("SUI input, splitting {amount_in} from the gas coin");
+        let budget = args
+            .initial_gas_budget()
+            .checked_add(amount_in)
+            .ok_or_else(|| QuantError::invalid_input("Gas budget plus SUI input overflows u64"))?;
+        let gas_inputs = select_gas_inputs(client, cache, owner, budget).await?;
+        return Ok((CoinSource::Gas, gas_inputs));
+    }
+
+    let token_inputs = match &args.token {
+        Some(token) => {
+            let token_object_id: Address = error::parse(token, "object id")?;
+            vec![cached_owned_input(client, cache, token_object_id).await?]
+        }
+        None => {
+            let coin_object_type = format!("0x2::coin::Coin<{input_type}>");
+            let (owned, cached) = owned_coins(client, cache, owner, &coin_object_type, false).await?;
+            let selected = match coins::select_input_coins(owned, amount_in, input_type) {
+                Err(_) if cached => {
+                    let (owned, _) = owned_coins(client, cache, owner, &coin_object_type, true).await?;
+                    coins::select_input_coins(owned, amount_in, input_type)?
+                }
+                selected => selected?,
+            };
+            status!("Selected {} input coins of {input_type}", selected.len());
+            selected.iter().map(|coin| coin.input()).collect()
+        }
+    };
+    let gas_inputs = select_gas_inputs(client, cache, owner, args.initial_gas_budget()).await?;
+    Ok((CoinSource::Coins(token_inputs), gas_inputs))
+}
+
+/// Gas payment covering `budget`.
+async fn select_gas_inputs(
+    client: &mut Client,
+    cache: &mut ObjectCache,
+    owner: &Address,
+    budget: u64,
+) -> Result<Vec<Input>, QuantError> {
+    let gas_coins = select_gas_coins(client, cache, owner, budget).await?;
+    Ok(gas_coins.iter().map(|coin| coin.input()).collect())
+}
+
+/// Gas coins covering `budget`.
+async fn select_gas_coins(
+    client: &mut Client,
+    cache: &mut ObjectCache,
+    owner: &Address,
+    budget: u64,
+) -> Result<Vec<coins::OwnedCoin>, QuantError> {
+    let (sui_coins, cached) = owned_coins(client, cache, owner, coins::SUI_COIN_TYPE, false).await?;
+    let gas_coins = match coins::select_gas_coins(sui_coins, budget) {
+        Err(_) if cached => {
+            let (sui_coins, _) = owned_coins(client, cache, owner, coins::SUI_COIN_TYPE, true).await?;
+            coins::select_gas_coins(sui_coins, budget)?
+        }
+        gas_coins => gas_coins?,
+    };
+
+    status!("Selected {} gas coins", gas_coins.len());
+    for coin in &gas_coins {
+        debug_main(&format!("[main] gas coin {} balance={}", coin.object_id, coin.balance));
+    }
+    Ok(gas_coins)
+}
+
+/// Coins of `coin_object_type` owned by `owner`, from `cache` unless
+/// `relist` or not listed yet; the flag tells whether they came from `cache`.
+///
+/// Coins received from other clients are only seen by a new listing, so a
+/// selection failing on cached coins retries with `relist`.
+async fn owned_coins(
+    client: &mut Client,
+    cache: &mut ObjectCache,
+    owner: &Address,
+    coin_object_type: &str,
+    relist: bool,
+) -> Result<(Vec<coins::OwnedCoin>, bool), QuantError> {
+    if !relist {
+        if let Some(coins) = cache.coins(owner, coin_object_type)? {
+            debug_main(&format!("[main] {} cached {coin_object_type} coins", coins.len()));
+            return Ok((coins, true));
+        }
+    }
+    let coins = coins::list_coins(client, owner, coin_object_type).await?;
+    cache.insert_coins(owner, coin_object_type, &coins);
+    Ok((coins, false))
+}
+
+/// `fixed` if given, else the epoch's reference gas price times `multiplier`.
+async fn resolve_gas_price(
+    client: &mut Client,
+    network: &NetworkConfig,
+    fixed: Option<u64>,
+    multiplier: f64,
+) -> Result<u64, QuantError> {
+    if let Some(gas_price) = fixed {
+        return Ok(gas_price);
+    }
+
+    let reference = gas_price::reference_gas_price(client, &network.name).await?;
+    let gas_price = gas_price::priority_gas_price(reference.reference_gas_price, multiplier)?;
+    status!(
+        "Gas price: {gas_price} (epoch {} reference {} x {multiplier})",
+        reference.epoch, reference.reference_gas_price
+    );
+    Ok(gas_price)
+}
+
+/// Simulate `tx` if the swap arguments ask for it, then sign and execute it
+/// unless this is a dry run.
+///
+/// With `--simulate` or `--sim-min-out`, a failed simulation or a simulated
+/// `output_type` gain below the threshold stops before signing. Without
+/// `--gas-budget`, the budget is set from the simulated gas cost. Executed
+/// swaps print a trade report timed from `start` to the execution response,
+/// after their checkpoint with `--wait-for-checkpoint`; a failed wait is
+/// recorded in the report and returned once the report is written.
+async fn submit_swap(
+    client: &mut Client,
+    network: &NetworkConfig,
+    cache: &mut ObjectCache,
+    private_key: &SuiKeyPair,
+    tx: Transaction,
+    (input_type, output_type): (&TypeTag, &TypeTag),
+    args: &SwapArgs,
+    start: Instant,
+) -> Result<(), QuantError> {
+    let mut tx = tx;
+    let owner = tx.sender;
+    let context = AbortContext::new(network, &tx);
+    let estimate_gas = args.gas_budget.is_none();
+    if args.dry_run || args.simulate || args.sim_min_out.is_some() || estimate_gas {
+        let report = simulate::simulate_transaction(client, &tx, &context).await?;
+        report.print();
+        if args.dry_run {
+            status!("Dry run, transaction not executed");
+            status!("Elapsed time: {:.3?}", start.elapsed());
+            return Ok(());
+        }
+
+        report.ensure_success()?;
+        if let Some(min_amount_out) = args.sim_min_out {
+            report.ensure_min_output(&owner, output_type, min_amount_out)?;
+        }
+        if estimate_gas {
+            let budget =
+                simulate::estimate_gas_budget(&report.outcome.gas_used, args.gas_margin, args.max_gas_budget)?;
+            status!("Estimated gas budget: {budget}");
+            tx.gas_payment.budget = budget;
+        }
+    }
+
+    let response = sign_and_execute(client, private_key, tx).await?;
+    let elapsed = start.elapsed();
+    debug_main(&format!("[main] execute response: {response:?}"));
+    let outcome = ExecutionOutcome::from_response(&response, &context)?;
+    outcome.print();
+    if let Some(transaction) = &response.transaction {
+        cache.apply_transaction(transaction);
+    }
+
+    // The swap is executed: a failed wait is recorded in the report and
+    // returned only after the report is written.
+    let (finality, finality_error) = match (&outcome.digest, args.wait_for_checkpoint) {
+        (Some(digest), true) => {
+            let timeout = Duration::from_secs(args.finality_timeout_secs);
+            status!("Waiting for checkpoint (timeout {timeout:?})...");
+            match finality::wait_for_checkpoint(client, digest, timeout, finality::DEFAULT_POLL_INTERVAL).await {
+                Ok(finality) => {
+                    status!("Finalized in checkpoint {}", finality.checkpoint);
+                    (Some(finality), None)
+                }
+                Err(e) => (None, Some(e)),
+            }
+        }
+        _ => (None, None),
+    };
+
+    if let Some(transaction) = &response.transaction {
+        let mut report = TradeReport::from_executed(transaction, &owner, input_type, output_type, elapsed)?;
+        if let Some(finality) = finality {
+            report.set_finality(finality);
+        }
+        if let Some(e) = &finality_error {
+            report.finality_error = Some(e.to_string());
+        }
+        match args.report {
+            ReportFormat::Table => report.print_table(),
+            ReportFormat::Json => println!("{}", report.to_json()?),
+        }
+        if let Some(path) = &args.report_file {
+            report.append_to(path)?;
+        }
+    }
+    if let Some(e) = finality_error {
+        return Err(e);
+    }
+    outcome.ensure_success()
+}
+
+/// Sign `tx` and submit it for execution.
+async fn sign_and_execute(
+    client: &mut Client,
+    private_key: &SuiKeyPair,
+    tx: Transaction,
+) -> Result<ExecuteTransactionResponse, QuantError> {
     let signature = private_key.sign_transaction(&tx)?;
     debug_main("[main] transaction signed");
 
-    // 7. Execute transaction.
     let mut exec_client = client.execution_client();
 
-    let mut request = sui_rpc::proto::sui::rpc::v2::ExecuteTransactionRequest::default();
+    let mut request = ExecuteTransactionRequest::default();
     request.transaction = Some(tx.into());
     request.signatures = vec![signature.into()];
+    request.read_mask = Some(FieldMask {
+        paths: vec![
+        "transaction.digest".to_string(),
+        "transaction.effects".to_string(),
+        "transaction.balance_changes".to_string(),
+        "transaction.objects.objects.object_id".to_string(),
+        "transaction.objects.objects.version".to_string(),
+        "transaction.objects.objects.object_type".to_string(),
+        "transaction.objects.objects.balance".to_string(),
+        "transaction.checkpoint".to_string(),
+        "transaction.timestamp".to_string(),
+        ],
+    });
 
     debug_main("[main] before execute_transaction");
     let response = exec_client.execute_transaction(request).await?;
     debug_main("[main] after execute_transaction");
-    let elapsed = start.elapsed();
-    println!("Transaction submitted, response: {:?}", response.into_inner());
-    println!("Elapsed time: {:.3?}", elapsed);
-    Ok(())
+    Ok(response.into_inner())
+}
+
+/// Shared object input; the caller picks `by_val` (mutable) or `by_ref`.
+fn shared_input(object_id: Address, obj: &Object) -> Result<Input, QuantError> {
+    Ok(Input::by_id(object_id)
+        .with_shared_kind()
+        .with_initial_shared_version(get_initail_shared_version(obj)?))
+}
+
+/// Shared object input from `cache`, fetched and cached on first use.
+async fn cached_shared_input(
+    client: &mut Client,
+    cache: &mut ObjectCache,
+    object_id: Address,
+) -> Result<Input, QuantError> {
+    if cache.get(&object_id).is_none() {
+        let obj = objects::fetch_object(client, object_id, objects::OBJECT_FIELDS).await?;
+        cache_shared(cache, object_id, &obj)?;
+    } else {
+        debug_main(&format!("[main] cached shared object {object_id}"));
+    }
+    cache
+        .input(&object_id)?
+        .ok_or_else(|| QuantError::ObjectNotFound(object_id.to_string()))
 }
 
-/// Decode Sui Ed25519 private key from bech32 "suiprivkey..." string.
-fn decode_sui_private_key(key_str: &str) -> Result<Ed25519PrivateKey, Box<dyn Error>> {
-    let (_hrp, data, _variant) = bech32::decode(key_str)?;
-    let bytes = Vec::<u8>::from_base32(&data)?;
+/// Owned object input from `cache`, fetched and cached on first use; later
+/// versions come from the effects of the transactions using it.
+async fn cached_owned_input(
+    client: &mut Client,
+    cache: &mut ObjectCache,
+    object_id: Address,
+) -> Result<Input, QuantError> {
+    if cache.get(&object_id).is_none() {
+        let obj = objects::fetch_object(client, object_id, objects::OBJECT_FIELDS).await?;
+        cache_owned(cache, object_id, &obj)?;
+    } else {
+        debug_main(&format!("[main] cached owned object {object_id}"));
+    }
+    cache
+        .input(&object_id)?
+        .ok_or_else(|| QuantError::ObjectNotFound(object_id.to_string()))
+}
 
-    if bytes.len() != 33 || bytes[0] != 0 {
-        return Err("Invalid Sui private key format".into());
+/// Read the shared objects `cache` handed out again and drop the entries
+/// whose initial shared version no longer matches, or that are gone or no
+/// longer shared. Entries that cannot be read are kept.
+async fn evict_stale_shared(client: &mut Client, cache: &mut ObjectCache) {
+    for object_id in cache.used_shared() {
+        let current = match objects::fetch_object(client, object_id, objects::OBJECT_FIELDS).await {
+            Ok(obj) => get_initail_shared_version(&obj).ok(),
+            Err(QuantError::ObjectNotFound(_)) => None,
+            Err(e) => {
+                debug_main(&format!("[main] keeping shared object {object_id}: {e}"));
+                continue;
+            }
+        };
+        let cached = cache.get(&object_id).cloned();
+        if current.map(|initial_shared_version| CachedObject::Shared { initial_shared_version }) != cached {
+            debug_main(&format!("[main] dropping stale shared object {object_id}"));
+            cache.remove(&object_id);
+        }
     }
+}
 
-    let pk_bytes: [u8; 32] = bytes[1..]
-        .try_into()
-        .map_err(|_| "Invalid Sui private key length")?;
+fn cache_shared(cache: &mut ObjectCache, object_id: Address, obj: &Object) -> Result<(), QuantError> {
+    let initial_shared_version = get_initail_shared_version(obj)?;
+    cache.insert(&object_id, CachedObject::Shared { initial_shared_version });
+    Ok(())
+}
+
+fn cache_owned(cache: &mut ObjectCache, object_id: Address, obj: &Object) -> Result<(), QuantError> {
+    let version = obj
+        .version
+        .ok_or_else(|| QuantError::invalid_response("Missing version for owned object"))?;
+    let digest = obj
+        .digest
+        .clone()
+        .ok_or_else(|| QuantError::invalid_response("Missing digest for owned object"))?;
+    cache.insert(&object_id, CachedObject::Owned { version, digest, coin: None });
+    Ok(())
+}
 
-    Ok(Ed25519PrivateKey::new(pk_bytes))
+/// `--token` as an object id list, empty without it.
+fn token_object_ids(args: &SwapArgs) -> Result<Vec<Address>, QuantError> {
+    args.token
+        .iter()
+        .map(|token| error::parse(token, "object id"))
+        .collect()
 }
 
-/// Fetch the first owned SUI coin object id for the given address.
-/// Only the object id is used; version and digest are not needed when using Input::by_id.
-async fn fetch_first_sui_gas_object_id(
+/// Fetch `pool_ids` with their contents in one batched read, together with
+/// the `shared_ids` and `owned_ids` missing from `cache`, which are cached
+/// for `cached_shared_input` / `cached_owned_input`.
+async fn load_swap_objects(
     client: &mut Client,
-    owner: &Address,
-) -> Result<Address, Box<dyn Error>> {
-    let mut state_client = client.state_client();
+    cache: &mut ObjectCache,
+    pool_ids: &[Address],
+    shared_ids: &[Address],
+    owned_ids: &[Address],
+) -> Result<Vec<Object>, QuantError> {
+    let uncached = |ids: &[Address]| -> Vec<Address> {
+        ids.iter().filter(|id| cache.get(id).is_none()).copied().collect()
+    };
+    let shared_ids = uncached(shared_ids);
+    let owned_ids = uncached(owned_ids);
+
+    let requests: Vec<(Address, &'static [&'static str])> = pool_ids
+        .iter()
+        .map(|id| (*id, objects::POOL_FIELDS))
+        .chain(shared_ids.iter().chain(&owned_ids).map(|id| (*id, objects::OBJECT_FIELDS)))
+        .collect();
+    debug_main(&format!("[main] loading {} objects in one batch", requests.len()));
+    let mut pools = objects::fetch_objects(client, &requests).await?;
 
-    let mut request = ListOwnedObjectsRequest::default();
-    request.owner = Some(owner.to_string());
-    request.page_size = Some(1000);
-    request.object_type = Some("0x2::coin::Coin<0x2::sui::SUI>".to_string());
+    let inputs = pools.split_off(pool_ids.len());
+    let (shared, owned) = inputs.split_at(shared_ids.len());
+    for (object_id, obj) in shared_ids.iter().zip(shared) {
+        cache_shared(cache, *object_id, obj)?;
+    }
+    for (object_id, obj) in owned_ids.iter().zip(owned) {
+        cache_owned(cache, *object_id, obj)?;
+    }
+    Ok(pools)
+}
+
+async fn run_quote(
+    client: &mut Client,
+    network: &NetworkConfig,
+    args: QuoteArgs,
+) -> Result<(), QuantError> {
+    let pool_object_id = parse_pool_id(network, &args.pool)?;
 
-    let mut mask = prost_types::FieldMask::default();
-    mask.paths = vec!["object_id".to_string()];
-    request.read_mask = Some(mask);
+    let pool_obj = objects::fetch_object(client, pool_object_id, objects::POOL_FIELDS).await?;
+    let swap_pool = resolve_swap_pool(network, &args.pool, &pool_obj)?;
+    let pool_state = PoolState::fetch(client, pool_object_id).await?;
 
-    let response = state_client.list_owned_objects(request).await?.into_inner();
-    println!("Owned SUI objects response: {:?}", response);
-    if response.objects.is_empty() {
-        return Err("No SUI gas objects found for this address".into());
+    println!("Pool: {} ({pool_object_id})", args.pool);
+    println!("Pool type: {}", pool_obj.object_type.unwrap_or_default());
+    println!("Coin A: {}", swap_pool.coin_type_a);
+    println!("Coin B: {}", swap_pool.coin_type_b);
+    println!("sqrt_price (Q64.64): {}", pool_state.sqrt_price);
+    println!("Liquidity: {}", pool_state.liquidity);
+    println!("Tick: {} (spacing {})", pool_state.tick_index, pool_state.tick_spacing);
+    println!("Fee rate: {} / 1000000", pool_state.fee_rate);
+    println!("Initialized ticks: {}", pool_state.ticks.len());
+    println!("Spot price (B per A, raw units): {}", pool_state.price());
+
+    let a_to_b = args.direction.a_to_b();
+    let quote = pool_state.quote(args.amount, a_to_b);
+    println!("-----------------------------------------");
+    println!("Direction: {:?}", args.direction);
+    println!("Amount in: {} (fee {})", quote.amount_in, quote.fee_amount);
+    println!("Amount out: {}", quote.amount_out);
+    println!("Price after (B per A, raw units): {}", quote.price_after);
+    println!("sqrt_price after (Q64.64): {}", quote.sqrt_price_after);
+    println!("Price impact: {:.2} bps", quote.price_impact_bps);
+    println!("Ticks crossed: {:?}", quote.ticks_crossed);
+    if quote.amount_in < args.amount {
+        println!("Warning: pool liquidity only absorbs {} of {}", quote.amount_in, args.amount);
     }
+    Ok(())
+}
 
-    // Use the first SUI coin object as gas.
-    let obj = &response.objects[0];
+async fn run_route(
+    client: &mut Client,
+    network: &NetworkConfig,
+    args: RouteArgs,
+) -> Result<(), QuantError> {
+    let graph = PoolGraph::load(client, network).await?;
+    println!("Loaded {} pools", graph.pools().len());
 
-    let oid_str = obj
-        .object_id
-        .as_ref()
-        .ok_or("Missing object_id field in ListOwnedObjectsResponse")?;
+    let from = error::parse(&args.from, "coin type")?;
+    let to = error::parse(&args.to, "coin type")?;
+    let routes = graph.find_routes(&from, &to, args.amount, args.max_hops)?;
+    if routes.is_empty() {
+        return Err(QuantError::InvalidInput(format!(
+            "No route of at most {} hops from {} to {}",
+            args.max_hops, args.from, args.to
+        )));
+    }
 
-    let oid: Address = oid_str.parse()?;
-    Ok(oid)
+    for route in routes {
+        match (route.gas_cost_in_output, route.net_amount_out()) {
+            (Some(gas_cost_in_output), Some(net_amount_out)) => println!(
+                "{}: out={} gas={} MIST (~{gas_cost_in_output} out) net={net_amount_out}",
+                route.pools.join(" -> "),
+                route.amount_out,
+                route.gas_cost
+            ),
+            _ => println!(
+                "{}: out={} gas={} MIST (not priced in {}) net=unknown",
+                route.pools.join(" -> "),
+                route.amount_out,
+                route.gas_cost,
+                args.to
+            ),
+        }
+    }
+    Ok(())
 }
 
-fn debug_main(msg: &str) {
-    if DEBUG_MAIN {
-        eprintln!("{msg}");
+async fn run_balances(client: &mut Client, key_source: KeySource<'_>) -> Result<(), QuantError> {
+    let (_, owner_address) = load_signer(key_source)?;
+
+    let coins = coins::list_coins(client, &owner_address, COIN_TYPE_FILTER).await?;
+
+    let mut balances: BTreeMap<String, (u64, usize)> = BTreeMap::new();
+    for coin in coins {
+        let entry = balances.entry(coin.object_type).or_insert((0, 0));
+        entry.0 += coin.balance;
+        entry.1 += 1;
     }
+
+    for (coin_type, (balance, count)) in balances {
+        println!("{coin_type}: {balance} ({count} objects)");
+    }
+    Ok(())
 }
 
-async fn fetch_object_details(
+async fn run_gas(client: &mut Client, key_source: KeySource<'_>) -> Result<(), QuantError> {
+    let (_, owner_address) = load_signer(key_source)?;
+
+    let coins = coins::list_coins(client, &owner_address, coins::SUI_COIN_TYPE).await?;
+    if coins.is_empty() {
+        return Err(QuantError::InsufficientBalance {
+            coin_type: coins::SUI_COIN_TYPE.to_string(),
+            required: 1,
+            available: 0,
+            coins: 0,
+        });
+    }
+
+    for coin in coins {
+        println!("{} version={} balance={}", coin.object_id, coin.version, coin.balance);
+    }
+    Ok(())
+}
+
+/// Merge each coin type's objects into its primary coin, one transaction
+/// per type, applying each transaction's effects to `cache`.
+///
+/// The primary coin is the one with the smallest object id. SUI is merged
+/// into its largest coin: up to `MAX_GAS_OBJECTS` coins pay gas together,
+/// so dust never has to cover the budget alone, and the rest are merged
+/// into the gas coin. A type with more than `MAX_MERGE_COINS` extra objects
+/// needs another run.
+async fn run_consolidate(
     client: &mut Client,
-    object_id: Address,
-) -> Result<Object, Box<dyn std::error::Error>> {
-    let mut ledger_client = client.ledger_client();
+    network: &NetworkConfig,
+    cache: &mut ObjectCache,
+    key_source: KeySource<'_>,
+    args: ConsolidateArgs,
+) -> Result<(), QuantError> {
+    let (private_key, owner_address) = load_signer(key_source)?;
+    let sui_type: TypeTag = error::parse("0x2::sui::SUI", "coin type")?;
+    let only_type: Option<TypeTag> = args
+        .coin_type
+        .as_deref()
+        .map(|coin_type| error::parse(coin_type, "coin type"))
+        .transpose()?;
+    let gas_price = resolve_gas_price(client, network, args.gas_price, args.gas_price_multiplier).await?;
 
-    // 如果你原本有 GetObjectRequest::new，可以這樣用：
-    let mut request = GetObjectRequest::new(&object_id);
+    let mut by_type: BTreeMap<String, Vec<coins::OwnedCoin>> = BTreeMap::new();
+    for coin in coins::list_coins(client, &owner_address, COIN_TYPE_FILTER).await? {
+        by_type.entry(coin.object_type.clone()).or_default().push(coin);
+    }
 
-    // 覆寫 read_mask，要求所有欄位
-    request.read_mask = Some(FieldMask {
-        paths: vec![
-        "object_id".to_string(),
-        "version".to_string(),
-        "digest".to_string(),
-        "owner".to_string(),
-        ],
-    });
+    for (object_type, mut type_coins) in by_type {
+        let coin_type = momentum::coin_object_type(&object_type)?;
+        if only_type.as_ref().is_some_and(|only| *only != coin_type) || type_coins.len() < 2 {
+            continue;
+        }
+
+        // SUI pays its own gas: its largest coins cover the budget and gas
+        // smashing merges up to MAX_GAS_OBJECTS of them into the first one.
+        // At least one coin is left for the MergeCoins command.
+        let is_sui = coin_type == sui_type;
+        let gas_count = if is_sui {
+            type_coins.sort_by_key(|coin| std::cmp::Reverse(coin.balance));
+            (type_coins.len() - 1).min(coins::MAX_GAS_OBJECTS)
+        } else {
+            type_coins.sort_by_key(|coin| coin.object_id);
+            1
+        };
+        let remaining = type_coins.len().saturating_sub(gas_count + coins::MAX_MERGE_COINS);
+        type_coins.truncate(gas_count + coins::MAX_MERGE_COINS);
+        let total: u64 = type_coins.iter().map(|coin| coin.balance).sum();
+        let sources = type_coins.split_off(gas_count);
+        let primary = &type_coins[0];
+        println!(
+            "{coin_type}: merging {} coins into {}",
+            type_coins.len() - 1 + sources.len(),
+            primary.object_id
+        );
+
+        let (target, gas_coins) = if is_sui {
+            (None, type_coins.clone())
+        } else {
+            // Gas coins are selected again for every type, against a rough
+            // cost of this merge: the previous merge changed their versions
+            // and balances.
+            let estimate = coins::estimate_merge_gas(sources.len(), gas_price).min(args.max_gas_budget);
+            (Some(primary), select_gas_coins(client, cache, &owner_address, estimate).await?)
+        };
+
+        // The simulation may spend whatever the gas coins hold, up to the
+        // maximum; the merge is then budgeted from what it used.
+        let gas_balance: u64 = gas_coins.iter().map(|coin| coin.balance).sum();
+        let simulation_budget = gas_balance.min(args.max_gas_budget);
+        let mut tx = coins::create_merge_transaction(
+            target,
+            &sources,
+            gas_coins.iter().map(|coin| coin.input()).collect(),
+            owner_address,
+            simulation_budget,
+            gas_price,
+        )?;
+
+        let context = AbortContext::new(network, &tx);
+        let report = simulate::simulate_transaction(client, &tx, &context).await?;
+        report.ensure_success()?;
+        tx.gas_payment.budget =
+            simulate::estimate_gas_budget(&report.outcome.gas_used, args.gas_margin, simulation_budget)?;
+        if args.dry_run {
+            report.print();
+            continue;
+        }
 
-    let response = ledger_client.get_object(request).await?.into_inner();
-    response.object.ok_or_else(|| "Object not found".into())
+        let response = sign_and_execute(client, &private_key, tx).await?;
+        let outcome = ExecutionOutcome::from_response(&response, &context)?;
+        if let Some(transaction) = &response.transaction {
+            cache.apply_transaction(transaction);
+        }
+        outcome.ensure_success()?;
+        let digest = outcome.digest.unwrap_or_default();
+        if is_sui {
+            println!("Primary coin {} now holds {total} minus gas ({digest})", primary.object_id);
+        } else {
+            println!("Primary coin {} now holds {total} ({digest})", primary.object_id);
+        }
+        if remaining > 0 {
+            println!("{remaining} {coin_type} coins left, run consolidate again");
+        }
+    }
+    Ok(())
+}
+
+/// Object id of a pool given by registry name or id.
+fn parse_pool_id(network: &NetworkConfig, pool: &str) -> Result<Address, QuantError> {
+    network
+        .pool_id(pool)
+        .parse()
+        .map_err(|_| {
+            QuantError::InvalidInput(format!(
+                "Pool '{pool}' is neither in the pool registry nor an object id"
+            ))
+        })
+}
+
+/// Coin types from the pool registry, else from the fetched pool object's type.
+fn resolve_swap_pool(
+    network: &NetworkConfig,
+    pool: &str,
+    pool_obj: &Object,
+) -> Result<SwapPool, QuantError> {
+    if let Some(pool_config) = network.find_pool(pool) {
+        return SwapPool::from_config(&network.packages, pool_config);
+    }
+
+    let pool_type = pool_obj
+        .object_type
+        .as_deref()
+        .ok_or_else(|| QuantError::invalid_response("Missing object_type for pool"))?;
+    SwapPool::from_pool_type(&network.packages, pool_type)
+}
+
+fn run_keys(key_source: KeySource<'_>, args: KeysArgs) -> Result<(), QuantError> {
+    let path = key_source
+        .encrypted_keystore
+        .map(|path| path.to_path_buf())
+        .or_else(encrypted_keystore::default_encrypted_keystore_path)
+        .ok_or_else(|| {
+            QuantError::Key("Cannot locate the encrypted keystore, pass --encrypted-keystore".to_string())
+        })?;
+
+    match args.command {
+        KeysCommand::Add { alias, generate } => {
+            let mut keystore = EncryptedKeystore::load_or_default(&path)?;
+            let mut flagged_key = if generate {
+                encrypted_keystore::generate_ed25519_key()
+            } else {
+                keystore::read_private_key_bytes(key_source)?
+            };
+            let passphrase = encrypted_keystore::read_new_passphrase(key_source.passphrase_file);
+            let added = passphrase.and_then(|passphrase| {
+                keystore
+                    .add(&alias, &flagged_key, &passphrase)
+                    .map(|key| format!("{} {} ({})", key.alias, key.address, key.scheme))
+            });
+            flagged_key.zeroize();
+
+            let added = added?;
+            keystore.save(&path)?;
+            println!("Added {added} to {}", path.display());
+        }
+        KeysCommand::List => {
+            let keystore = EncryptedKeystore::load_or_default(&path)?;
+            for key in keystore.keys() {
+                println!("{}\t{}\t{}", key.alias, key.address, key.scheme);
+            }
+        }
+        KeysCommand::Export => {
+            let keystore = EncryptedKeystore::load(&path)?;
+            let key = keystore.find(key_source.select)?;
+            let passphrase = encrypted_keystore::read_passphrase(
+                key_source.passphrase_file,
+                &format!("Passphrase for {} ({}): ", key.alias, key.address),
+            )?;
+
+            let mut flagged_key = key.decrypt(&passphrase)?;
+            let encoded = keystore::encode_sui_private_key(&flagged_key);
+            flagged_key.zeroize();
+            println!("{}", encoded?);
+        }
+    }
+    Ok(())
 }
-fn get_initail_shared_version(
-    obj: &sui_rpc::proto::sui::rpc::v2::Object,
-) -> Result<u64, Box<dyn Error>> {
-    println!("Object details: {:?}", obj);
-    if let Some(ref owner) = obj.owner {
-            return Ok(owner.version());
+
+fn debug_main(msg: &str) {
+    if DEBUG_MAIN {
+        eprintln!("{msg}");
     }
-    Err("Object is not shared or missing owner field".into())
-}
\ No newline at end of file
+}
+
+/// JSON contents of a pool fetched with `objects::POOL_FIELDS`.
+fn pool_json(pool_obj: &Object) -> Result<&prost_types::Value, QuantError> {
+    let json: &prost_types::Value = pool_obj
+        .json
+        .as_ref()
+        .ok_or_else(|| QuantError::invalid_response("Missing json in pool object"))?;
+    Ok(json)
+}
+
+/// Initial shared version of a shared object; any other owner is an error.
+fn get_initail_shared_version(obj: &Object) -> Result<u64, QuantError> {
+    let owner = obj
+        .owner
+        .as_ref()
+        .filter(|owner| owner.kind() == OwnerKind::Shared)
+        .ok_or_else(|| QuantError::WrongOwnership {
+            object_id: obj.object_id.clone().unwrap_or_default(),
+            expected: "shared",
+        })?;
+    owner.version.ok_or_else(|| {
+        QuantError::InvalidResponse(format!(
+            "Missing initial shared version for {}",
+            obj.object_id.as_deref().unwrap_or_default()
+        ))
+    })
+}
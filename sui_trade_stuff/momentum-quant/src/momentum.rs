@@ -1,47 +1,269 @@
-use std::error::Error;
-
 use sui_sdk_types::{Address, Argument, TypeTag};
 use sui_transaction_builder::unresolved::Input;
 use sui_transaction_builder::{Function, Serialized, TransactionBuilder};
 
+use crate::config::{PackageConfig, PoolConfig};
+use crate::error::{self, QuantError};
+use crate::slippage::SlippageLimits;
+
 /// Enable / disable debug logs inside momentum module.
 const DEBUG_MOMENTUM: bool = true;
 
-/// Move package addresses and type tags.
-const MOMENTUM_TRADE_PACKAGE: &str =
-    "0xcf60a40f45d46fc1e828871a647c1e25a0915dec860d2662eb10fdb382c3c1d1";
-const MOMENTUM_SLIPPAGE_PACKAGE: &str =
-    "0x8add2f0f8bc9748687639d7eb59b2172ba09a0172d9e63c029e23a7dbdb6abe6";
+/// Coin type paid as gas.
+const SUI_TYPE: &str = "0x2::sui::SUI";
+
+/// Module and struct name of Momentum CLMM pools, `<package>::pool::Pool<A, B>`.
+const POOL_STRUCT: &str = "::pool::Pool<";
+
+/// `0x2::coin::Coin<` in its short and fully expanded forms.
+const COIN_STRUCT: &str = "0x2::coin::Coin<";
+const COIN_STRUCT_LONG: &str =
+    "0x0000000000000000000000000000000000000000000000000000000000000002::coin::Coin<";
+
+/// Where the swap input is split from.
+#[derive(Clone, Debug)]
+pub enum CoinSource {
+    /// The gas coin, for SUI inputs; a wallet holding a single SUI coin
+    /// can still trade it.
+    Gas,
+    /// Owned coins of the input type, merged into the first one when the
+    /// balance is spread over several coins.
+    Coins(Vec<Input>),
+}
+
+impl CoinSource {
+    /// Add the source to the builder and return the coin to split from.
+    fn argument(self, tx: &mut TransactionBuilder) -> Result<Argument, QuantError> {
+        match self {
+            CoinSource::Gas => Ok(Argument::Gas),
+            CoinSource::Coins(inputs) => {
+                let mut inputs = inputs.into_iter();
+                let primary = inputs
+                    .next()
+                    .ok_or_else(|| QuantError::invalid_input("No input coins to swap"))?;
+                let primary = tx.input(primary);
+                let rest: Vec<Argument> = inputs.map(|input| tx.input(input)).collect();
+                if !rest.is_empty() {
+                    debug_momentum(&format!("[CoinSource] merging {} input coins", rest.len()));
+                    tx.merge_coins(primary, rest);
+                }
+                Ok(primary)
+            }
+        }
+    }
+}
+
+/// Whether `coin_type` is `0x2::sui::SUI`, whose swaps split from the gas coin.
+pub fn is_sui(coin_type: &TypeTag) -> bool {
+    SUI_TYPE.parse::<TypeTag>().is_ok_and(|sui| sui == *coin_type)
+}
+
+/// How much to trade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapAmount {
+    /// Sell exactly `amount_in` of the input coin.
+    ExactIn(u64),
+    /// Buy exactly `amount_out` of the output coin, spending at most
+    /// `max_amount_in`; the unspent input goes back to the sender.
+    ExactOut { amount_out: u64, max_amount_in: u64 },
+}
+
+impl SwapAmount {
+    /// `by_amount_in` argument of `trade::flash_swap`.
+    pub fn by_amount_in(&self) -> bool {
+        matches!(self, SwapAmount::ExactIn(_))
+    }
+
+    /// `amount` argument of `trade::flash_swap`.
+    pub fn flash_swap_amount(&self) -> u64 {
+        match *self {
+            SwapAmount::ExactIn(amount_in) => amount_in,
+            SwapAmount::ExactOut { amount_out, .. } => amount_out,
+        }
+    }
+
+    /// Input split off the sender's coin; the repayment is taken from it.
+    pub fn max_amount_in(&self) -> u64 {
+        match *self {
+            SwapAmount::ExactIn(amount_in) => amount_in,
+            SwapAmount::ExactOut { max_amount_in, .. } => max_amount_in,
+        }
+    }
+}
+
+/// Packages and coin types needed to swap through one Momentum pool.
+#[derive(Clone, Debug)]
+pub struct SwapPool {
+    pub trade_package: Address,
+    pub slippage_package: Address,
+    pub coin_type_a: TypeTag,
+    pub coin_type_b: TypeTag,
+}
 
-const A_TOKEN_TYPE: &str =
-    "0x2::sui::SUI";
-const B_TOKEN_TYPE: &str =
-    "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC";
+impl SwapPool {
+    pub fn new(
+        packages: &PackageConfig,
+        coin_type_a: TypeTag,
+        coin_type_b: TypeTag,
+    ) -> Result<SwapPool, QuantError> {
+        Ok(SwapPool {
+            trade_package: error::parse(&packages.trade, "trade package id")?,
+            slippage_package: error::parse(&packages.slippage, "slippage package id")?,
+            coin_type_a,
+            coin_type_b,
+        })
+    }
 
-/// Global object ids used inside the transaction (clock, versioned object, etc.).
+    /// Coin types from a pool registry entry.
+    pub fn from_config(
+        packages: &PackageConfig,
+        pool: &PoolConfig,
+    ) -> Result<SwapPool, QuantError> {
+        SwapPool::new(
+            packages,
+            error::parse(&pool.coin_type_a, "coin type")?,
+            error::parse(&pool.coin_type_b, "coin type")?,
+        )
+    }
 
+    /// Coin types from the pool object's type, `<package>::pool::Pool<A, B>`.
+    pub fn from_pool_type(
+        packages: &PackageConfig,
+        pool_type: &str,
+    ) -> Result<SwapPool, QuantError> {
+        let (coin_type_a, coin_type_b) = parse_pool_coin_types(pool_type)?;
+        SwapPool::new(
+            packages,
+            error::parse(coin_type_a, "coin type")?,
+            error::parse(coin_type_b, "coin type")?,
+        )
+    }
+}
+
+/// Split `<package>::pool::Pool<A, B>` into `A` and `B`, which may themselves
+/// be generic.
+fn parse_pool_coin_types(pool_type: &str) -> Result<(&str, &str), QuantError> {
+    let invalid = || QuantError::InvalidResponse(format!("Not a Momentum pool type: {pool_type}"));
+
+    let start = pool_type.find(POOL_STRUCT).ok_or_else(invalid)? + POOL_STRUCT.len();
+    let params = pool_type[start..].strip_suffix('>').ok_or_else(invalid)?;
+
+    // Exactly one top-level comma separates the two type parameters.
+    let mut depth = 0usize;
+    let mut split = None;
+    for (i, c) in params.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.checked_sub(1).ok_or_else(invalid)?,
+            ',' if depth == 0 => {
+                if split.replace(i).is_some() {
+                    return Err(invalid());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let i = split.ok_or_else(invalid)?;
+    let (a, b) = (params[..i].trim(), params[i + 1..].trim());
+    if depth != 0 || a.is_empty() || b.is_empty() {
+        return Err(invalid());
+    }
+    Ok((a, b))
+}
+
+/// One pool of a multi-hop route and the direction it is traded in.
+#[derive(Clone, Debug)]
+pub struct RouteHop {
+    pub swap_pool: SwapPool,
+    /// true for A -> B, false for B -> A.
+    pub a_to_b: bool,
+}
+
+impl RouteHop {
+    pub fn input_type(&self) -> &TypeTag {
+        if self.a_to_b {
+            &self.swap_pool.coin_type_a
+        } else {
+            &self.swap_pool.coin_type_b
+        }
+    }
+
+    pub fn output_type(&self) -> &TypeTag {
+        if self.a_to_b {
+            &self.swap_pool.coin_type_b
+        } else {
+            &self.swap_pool.coin_type_a
+        }
+    }
+}
+
+/// One leg of a split order: `amount_in` sold through one pool of the pair.
+#[derive(Clone, Debug)]
+pub struct SplitLeg {
+    pub hop: RouteHop,
+    pub amount_in: u64,
+}
+
+/// Chain `pools` starting from `input_type`, picking each hop's direction
+/// from the coin type the previous hop produces.
+pub fn plan_route(pools: Vec<SwapPool>, input_type: &TypeTag) -> Result<Vec<RouteHop>, QuantError> {
+    let mut hops = Vec::with_capacity(pools.len());
+    let mut current = input_type.clone();
+
+    for swap_pool in pools {
+        let a_to_b = if swap_pool.coin_type_a == current {
+            true
+        } else if swap_pool.coin_type_b == current {
+            false
+        } else {
+            return Err(QuantError::InvalidInput(format!(
+                "Route breaks at pool {}/{}: it does not trade {current}",
+                swap_pool.coin_type_a, swap_pool.coin_type_b
+            )));
+        };
+        let hop = RouteHop { swap_pool, a_to_b };
+        current = hop.output_type().clone();
+        hops.push(hop);
+    }
+    Ok(hops)
+}
+
+/// Coin type `T` of a `0x2::coin::Coin<T>` object type.
+pub fn coin_object_type(object_type: &str) -> Result<TypeTag, QuantError> {
+    let inner = object_type
+        .strip_prefix(COIN_STRUCT)
+        .or_else(|| object_type.strip_prefix(COIN_STRUCT_LONG))
+        .and_then(|rest| rest.strip_suffix('>'))
+        .ok_or_else(|| QuantError::InvalidInput(format!("Not a coin object type: {object_type}")))?;
+    error::parse(inner, "coin type")
+}
 
 /// Build a swap transaction using only Input::by_id for all object inputs.
 ///
-/// - `token_input`: input for the token coin.
+/// - `token`: coins the input is split from, the gas coin for SUI.
 /// - `pool_input`: input for the pool shared object.
-/// - `gas_object_id`: object id of the SUI gas coin.
-/// - `amount`: input token amount.
+/// - `gas_inputs`: SUI gas coins, merged into the first one by the transaction.
+/// - `amount`: exact input, or exact output with a maximum input.
 /// - `direction`: true for A -> B, false for B -> A.
 /// - `sender`: transaction sender.
 /// - `gas_budget`, `gas_price`: gas configuration.
+/// - `swap_pool`: Momentum packages and pool coin types.
+/// - `limits`: sqrt price limit and minimum output.
 pub fn create_swap_transaction(
-    token_input: Input,
+    token: CoinSource,
     pool_input: Input,
-    gas_input: Input,
-    amount: u64,
+    gas_inputs: Vec<Input>,
+    amount: SwapAmount,
     direction: bool,
     sender: Address,
     gas_budget: u64,
     gas_price: u64,
     clock_input: Input,
     version_input: Input,
-) -> Result<sui_sdk_types::Transaction, Box<dyn Error>> {
+    swap_pool: &SwapPool,
+    limits: &SlippageLimits,
+) -> Result<sui_sdk_types::Transaction, QuantError> {
     debug_momentum("[create_swap_transaction] start");
 
     let mut tx = TransactionBuilder::new();
@@ -55,51 +277,160 @@ pub fn create_swap_transaction(
         "[create_swap_transaction] sender={sender}, gas_budget={gas_budget}, gas_price={gas_price}"
     ));
 
-    // Gas input via by_id.
+    // Gas inputs via by_id.
     debug_momentum(&format!(
-        "[create_swap_transaction] adding gas object by_id: {gas_input:?}"
+        "[create_swap_transaction] adding {} gas objects by_id",
+        gas_inputs.len()
     ));
-    println!("{gas_input:?}");
-    tx.add_gas_objects(vec![gas_input]);
+    tx.add_gas_objects(gas_inputs);
 
     // Token input and pool input via by_id.
     debug_momentum("[create_swap_transaction] adding token and pool inputs");
-    let token_input = tx.input(token_input);
+    let token_input = token.argument(&mut tx)?;
     let pool_input = tx.input(pool_input);
     let clock_input = tx.input(clock_input);
     let version_input = tx.input(version_input);
     // Build swap logic.
     debug_momentum("[create_swap_transaction] before swap()");
-    swap(&mut tx, token_input, amount, direction, pool_input, sender, clock_input, version_input)?;
+    swap(
+        &mut tx,
+        token_input,
+        amount,
+        direction,
+        pool_input,
+        sender,
+        clock_input,
+        version_input,
+        swap_pool,
+        limits,
+    )?;
     debug_momentum("[create_swap_transaction] after swap()");
 
     // Finalize transaction.
     debug_momentum("[create_swap_transaction] before finish()");
-    let transaction = tx.finish()?;
+    let transaction = tx.finish().map_err(|e| QuantError::Build(e.to_string()))?;
     debug_momentum("[create_swap_transaction] after finish()");
 
     Ok(transaction)
 }
 
+/// Build a multi-hop swap transaction: one pool input per hop, in route order.
+///
+/// - `min_amount_out`: minimum final output, 0 to skip the check.
+pub fn create_route_transaction(
+    token: CoinSource,
+    pool_inputs: Vec<Input>,
+    gas_inputs: Vec<Input>,
+    amount_in: u64,
+    sender: Address,
+    gas_budget: u64,
+    gas_price: u64,
+    clock_input: Input,
+    version_input: Input,
+    hops: &[RouteHop],
+    min_amount_out: u64,
+) -> Result<sui_sdk_types::Transaction, QuantError> {
+    debug_momentum("[create_route_transaction] start");
+
+    let mut tx = TransactionBuilder::new();
+
+    tx.set_sender(sender);
+    tx.set_gas_budget(gas_budget);
+    tx.set_gas_price(gas_price);
+    tx.add_gas_objects(gas_inputs);
+
+    let token_input = token.argument(&mut tx)?;
+    let pools: Vec<Argument> = pool_inputs.into_iter().map(|pool| tx.input(pool)).collect();
+    let clock_input = tx.input(clock_input);
+    let version_input = tx.input(version_input);
+
+    swap_route(
+        &mut tx,
+        token_input,
+        amount_in,
+        &pools,
+        hops,
+        sender,
+        clock_input,
+        version_input,
+        min_amount_out,
+    )?;
+
+    let transaction = tx.finish().map_err(|e| QuantError::Build(e.to_string()))?;
+    debug_momentum("[create_route_transaction] after finish()");
+
+    Ok(transaction)
+}
+
+/// Build a split-order transaction: one pool input per leg, in leg order.
+pub fn create_split_transaction(
+    token: CoinSource,
+    pool_inputs: Vec<Input>,
+    gas_inputs: Vec<Input>,
+    sender: Address,
+    gas_budget: u64,
+    gas_price: u64,
+    clock_input: Input,
+    version_input: Input,
+    legs: &[SplitLeg],
+    limits: &[SlippageLimits],
+    min_amount_out: u64,
+) -> Result<sui_sdk_types::Transaction, QuantError> {
+    debug_momentum("[create_split_transaction] start");
+
+    let mut tx = TransactionBuilder::new();
+
+    tx.set_sender(sender);
+    tx.set_gas_budget(gas_budget);
+    tx.set_gas_price(gas_price);
+    tx.add_gas_objects(gas_inputs);
+
+    let token_input = token.argument(&mut tx)?;
+    let pools: Vec<Argument> = pool_inputs.into_iter().map(|pool| tx.input(pool)).collect();
+    let clock_input = tx.input(clock_input);
+    let version_input = tx.input(version_input);
+
+    swap_split(
+        &mut tx,
+        token_input,
+        &pools,
+        legs,
+        sender,
+        clock_input,
+        version_input,
+        limits,
+        min_amount_out,
+    )?;
+
+    let transaction = tx.finish().map_err(|e| QuantError::Build(e.to_string()))?;
+    debug_momentum("[create_split_transaction] after finish()");
+
+    Ok(transaction)
+}
+
 /// Core swap logic built on top of TransactionBuilder.
 ///
 /// All objects are added to the builder using Input::by_id or Serialized.
+/// The pool's coin types come from `swap_pool`, so any Momentum pool works.
 pub fn swap(
     tx: &mut TransactionBuilder,
     token_input: Argument,
-    amount: u64,
+    amount: SwapAmount,
     direction: bool,
     pool: Argument,
     sender: Address,
     clock_object: Argument,
     versioned_object: Argument,
-) -> Result<(), Box<dyn Error>> {
+    swap_pool: &SwapPool,
+    limits: &SlippageLimits,
+) -> Result<(), QuantError> {
     debug_momentum(&format!(
-        "[swap] start, amount={amount}, direction={direction}, sender={sender}"
+        "[swap] start, amount={amount:?}, direction={direction}, sender={sender}"
     ));
 
-    let a_token_type: TypeTag = A_TOKEN_TYPE.parse()?;
-    let b_token_type: TypeTag = B_TOKEN_TYPE.parse()?;
+    let a_token_type = swap_pool.coin_type_a.clone();
+    let b_token_type = swap_pool.coin_type_b.clone();
+    let slippage_package = swap_pool.slippage_package;
 
     // Clock object (global).
     debug_momentum("[swap] adding clock object");
@@ -108,40 +439,304 @@ pub fn swap(
     // Versioned object (global configuration for the pool).
     debug_momentum("[swap] adding versioned object");
     // 1. Split token coins (create an internal split for repayment later).
-    debug_momentum("[swap] step 1: split_coins");
-    let amount_arg = tx.input(Serialized(&amount));
+    // For exact output this is the maximum input: repaying a larger debt
+    // from it aborts the transaction.
+    let max_amount_in = amount.max_amount_in();
+    debug_momentum(&format!("[swap] step 1: split_coins, max_amount_in={max_amount_in}"));
+    let amount_arg = tx.input(Serialized(&max_amount_in));
     let split_coin = tx.split_coins(token_input, vec![amount_arg]);
 
-    // 2. Flash swap.
-    let sqrt_price_limit: u128 = if direction {
-        // A -> B
-        4_295_048_016u128
-    } else {
-        // B -> A
-        79_226_673_515_401_279_992_447_579_055u128
-    };
+    // 2-10. Flash swap and repay the receipt debt from the split coin.
+    let sqrt_price_limit: u128 = limits.sqrt_price_limit;
+    let flash_amount_arg = tx.input(Serialized(&amount.flash_swap_amount()));
+    let output_coin = flash_swap_and_repay(
+        tx,
+        split_coin,
+        amount.by_amount_in(),
+        flash_amount_arg,
+        direction,
+        pool,
+        clock_object,
+        versioned_object,
+        swap_pool,
+        sqrt_price_limit,
+    )?;
+
+    // 11. Slippage check: the pool price must not have crossed the limit.
+    let slippage_limit: u128 = limits.sqrt_price_limit;
+
+    debug_momentum(&format!(
+        "[swap] step 11: assert_slippage, slippage_limit={slippage_limit}"
+    ));
+
+    let slippage_limit_arg = tx.input(Serialized(&slippage_limit));
+    let direction_arg_2 = tx.input(Serialized(&direction));
+
+    tx.move_call(
+        Function::new(
+            slippage_package,
+            error::parse("slippage_check", "Move identifier")?,
+            error::parse("assert_slippage", "Move identifier")?,
+            vec![a_token_type, b_token_type],
+        ),
+        vec![pool, slippage_limit_arg, direction_arg_2],
+    );
+
+    // 12. Minimum output check: splitting `min_amount_out` aborts if the
+    // output coin holds less, then the split part is merged back.
+    if limits.min_amount_out > 0 {
+        debug_momentum(&format!(
+            "[swap] step 12: min output check, min_amount_out={}",
+            limits.min_amount_out
+        ));
+        assert_min_amount_out(tx, output_coin, limits.min_amount_out);
+    }
+
+    // 13. Transfer output coin + remaining split coin back to sender.
+    debug_momentum("[swap] step 13: transfer_objects back to sender");
+    let sender_arg = tx.input(Serialized(&sender));
+    tx.transfer_objects(vec![output_coin, split_coin], sender_arg);
+
+    debug_momentum("[swap] end");
+    Ok(())
+}
+
+/// Swap `amount_in` along `hops`, each hop's output coin feeding the next hop.
+///
+/// Intermediate hops run without a price limit; only the final output is
+/// checked against `min_amount_out`. The output and any leftover of the
+/// input and intermediate coins are transferred to `sender`.
+pub fn swap_route(
+    tx: &mut TransactionBuilder,
+    token_input: Argument,
+    amount_in: u64,
+    pools: &[Argument],
+    hops: &[RouteHop],
+    sender: Address,
+    clock_object: Argument,
+    versioned_object: Argument,
+    min_amount_out: u64,
+) -> Result<(), QuantError> {
+    if hops.is_empty() || hops.len() != pools.len() {
+        return Err(QuantError::invalid_input("A route needs one pool argument per hop"));
+    }
+    debug_momentum(&format!(
+        "[swap_route] start, amount_in={amount_in}, hops={}, sender={sender}",
+        hops.len()
+    ));
+
+    // 1. Split the route input off the token coin.
+    let amount_arg = tx.input(Serialized(&amount_in));
+    let split_coin = tx.split_coins(token_input, vec![amount_arg]);
+
+    // 2. One flash swap / repay per hop; from the second hop on, the amount
+    // is the value of the previous hop's output coin.
+    let mut leftovers = vec![split_coin];
+    let mut input_coin = split_coin;
+    let mut amount = amount_arg;
+    for (i, (hop, pool)) in hops.iter().zip(pools).enumerate() {
+        debug_momentum(&format!(
+            "[swap_route] hop {i}: {} -> {}",
+            hop.input_type(),
+            hop.output_type()
+        ));
+        if i > 0 {
+            amount = tx.move_call(
+                Function::new(
+                    error::parse("0x2", "package id")?,
+                    error::parse("coin", "Move identifier")?,
+                    error::parse("value", "Move identifier")?,
+                    vec![hop.input_type().clone()],
+                ),
+                vec![input_coin],
+            );
+            leftovers.push(input_coin);
+        }
+
+        input_coin = flash_swap_and_repay(
+            tx,
+            input_coin,
+            true,
+            amount,
+            hop.a_to_b,
+            *pool,
+            clock_object,
+            versioned_object,
+            &hop.swap_pool,
+            SlippageLimits::unbounded(hop.a_to_b).sqrt_price_limit,
+        )?;
+    }
+    let output_coin = input_coin;
+
+    // 3. Minimum output check on the final coin only.
+    if min_amount_out > 0 {
+        debug_momentum(&format!(
+            "[swap_route] min output check, min_amount_out={min_amount_out}"
+        ));
+        assert_min_amount_out(tx, output_coin, min_amount_out);
+    }
+
+    // 4. Transfer the output and the (normally empty) leftovers to sender.
+    let sender_arg = tx.input(Serialized(&sender));
+    let mut coins = vec![output_coin];
+    coins.extend(leftovers);
+    tx.transfer_objects(coins, sender_arg);
+
+    debug_momentum("[swap_route] end");
+    Ok(())
+}
+
+/// Sell one input coin through several pools of the same pair at once.
+///
+/// Each leg gets its own split of `token_input`, its own flash swap / repay
+/// sequence and its own `assert_slippage` against `limits[i]`. The leg
+/// outputs are merged into one coin, checked against `min_amount_out` and
+/// transferred to `sender` with the leftover splits.
+pub fn swap_split(
+    tx: &mut TransactionBuilder,
+    token_input: Argument,
+    pools: &[Argument],
+    legs: &[SplitLeg],
+    sender: Address,
+    clock_object: Argument,
+    versioned_object: Argument,
+    limits: &[SlippageLimits],
+    min_amount_out: u64,
+) -> Result<(), QuantError> {
+    if legs.is_empty() || legs.len() != pools.len() || legs.len() != limits.len() {
+        return Err(QuantError::invalid_input(
+            "A split order needs one pool argument and one limit per leg",
+        ));
+    }
+    debug_momentum(&format!(
+        "[swap_split] start, legs={:?}, sender={sender}",
+        legs.iter().map(|leg| leg.amount_in).collect::<Vec<_>>()
+    ));
+
+    // 1. Split every leg's input off the token coin in one command.
+    let amount_args: Vec<Argument> = legs
+        .iter()
+        .map(|leg| tx.input(Serialized(&leg.amount_in)))
+        .collect();
+    let split_result = tx.split_coins(token_input, amount_args.clone());
+
+    // 2. One flash swap / repay / price check per leg.
+    let mut split_coins = Vec::with_capacity(legs.len());
+    let mut output_coins = Vec::with_capacity(legs.len());
+    for (i, leg) in legs.iter().enumerate() {
+        let split_coin = split_result
+            .nested(i as u16)
+            .ok_or_else(|| QuantError::Build("split_coins result missing a leg".to_string()))?;
+        let hop = &leg.hop;
+        debug_momentum(&format!(
+            "[swap_split] leg {i}: amount_in={}, a_to_b={}",
+            leg.amount_in, hop.a_to_b
+        ));
+
+        let output_coin = flash_swap_and_repay(
+            tx,
+            split_coin,
+            true,
+            amount_args[i],
+            hop.a_to_b,
+            pools[i],
+            clock_object,
+            versioned_object,
+            &hop.swap_pool,
+            limits[i].sqrt_price_limit,
+        )?;
+
+        let slippage_limit_arg = tx.input(Serialized(&limits[i].sqrt_price_limit));
+        let direction_arg = tx.input(Serialized(&hop.a_to_b));
+        tx.move_call(
+            Function::new(
+                hop.swap_pool.slippage_package,
+                error::parse("slippage_check", "Move identifier")?,
+                error::parse("assert_slippage", "Move identifier")?,
+                vec![hop.swap_pool.coin_type_a.clone(), hop.swap_pool.coin_type_b.clone()],
+            ),
+            vec![pools[i], slippage_limit_arg, direction_arg],
+        );
+
+        split_coins.push(split_coin);
+        output_coins.push(output_coin);
+    }
+
+    // 3. Merge the leg outputs and check the total.
+    let output_coin = output_coins[0];
+    if output_coins.len() > 1 {
+        tx.merge_coins(output_coin, output_coins[1..].to_vec());
+    }
+    if min_amount_out > 0 {
+        debug_momentum(&format!(
+            "[swap_split] min output check, min_amount_out={min_amount_out}"
+        ));
+        assert_min_amount_out(tx, output_coin, min_amount_out);
+    }
+
+    // 4. Transfer the output and the leftover splits to sender.
+    let sender_arg = tx.input(Serialized(&sender));
+    let mut coins = vec![output_coin];
+    coins.extend(split_coins);
+    tx.transfer_objects(coins, sender_arg);
+
+    debug_momentum("[swap_split] end");
+    Ok(())
+}
+
+/// Abort unless `coin` holds at least `min_amount_out`: splitting that much
+/// fails otherwise, then the split part is merged back.
+///
+/// `outcome::AbortContext` recognizes this command pair to report its
+/// failure as exceeded slippage; keep the two commands adjacent.
+fn assert_min_amount_out(tx: &mut TransactionBuilder, coin: Argument, min_amount_out: u64) {
+    let min_amount_out_arg = tx.input(Serialized(&min_amount_out));
+    let min_out_coin = tx.split_coins(coin, vec![min_amount_out_arg]);
+    tx.merge_coins(coin, vec![min_out_coin]);
+}
 
+/// Steps 2-10 of a swap: flash swap `amount` through `pool`, turn the
+/// output balance into a coin and repay the receipt debt from `input_coin`.
+///
+/// `amount` is a pure `u64` or the result of an earlier command. Returns the
+/// output coin.
+fn flash_swap_and_repay(
+    tx: &mut TransactionBuilder,
+    input_coin: Argument,
+    by_amount_in: bool,
+    amount: Argument,
+    direction: bool,
+    pool: Argument,
+    clock_object: Argument,
+    versioned_object: Argument,
+    swap_pool: &SwapPool,
+    sqrt_price_limit: u128,
+) -> Result<Argument, QuantError> {
+    let a_token_type = swap_pool.coin_type_a.clone();
+    let b_token_type = swap_pool.coin_type_b.clone();
+    let trade_package = swap_pool.trade_package;
+
+    // 2. Flash swap, stopping at the caller's price limit.
     debug_momentum(&format!(
         "[swap] step 2: flash_swap, sqrt_price_limit={sqrt_price_limit}"
     ));
 
     let direction_arg = tx.input(Serialized(&direction));
-    let by_amount_in_arg = tx.input(Serialized(&true));
-    let amount_arg_2 = tx.input(Serialized(&amount));
+    let by_amount_in_arg = tx.input(Serialized(&by_amount_in));
     let sqrt_price_limit_arg = tx.input(Serialized(&sqrt_price_limit));
 
     let flash_swap_result = tx.move_call(
         Function::new(
-            MOMENTUM_TRADE_PACKAGE.parse()?,
-            "trade".parse()?,
-            "flash_swap".parse()?,
+            trade_package,
+            error::parse("trade", "Move identifier")?,
+            error::parse("flash_swap", "Move identifier")?,
             vec![a_token_type.clone(), b_token_type.clone()],
         ),
         vec![
             pool,
             direction_arg,
             by_amount_in_arg,
-            amount_arg_2,
+            amount,
             sqrt_price_limit_arg,
             clock_object,
             versioned_object,
@@ -153,13 +748,13 @@ pub fn swap(
     // flash_swap returns: balance_a, balance_b, receipt.
     let flash_swap_r1 = flash_swap_result
         .nested(0)
-        .expect("flash_swap result index 0 missing");
+        .ok_or_else(|| QuantError::Build("flash_swap result index 0 missing".to_string()))?;
     let flash_swap_r2 = flash_swap_result
         .nested(1)
-        .expect("flash_swap result index 1 missing");
+        .ok_or_else(|| QuantError::Build("flash_swap result index 1 missing".to_string()))?;
     let flash_swap_r3 = flash_swap_result
         .nested(2)
-        .expect("flash_swap result index 2 missing");
+        .ok_or_else(|| QuantError::Build("flash_swap result index 2 missing".to_string()))?;
 
     // 3. Destroy the zero balance (input side after swap).
     debug_momentum("[swap] step 3: destroy_zero & select output_balance");
@@ -182,9 +777,9 @@ pub fn swap(
 
     tx.move_call(
         Function::new(
-            "0x2".parse()?,
-            "balance".parse()?,
-            "destroy_zero".parse()?,
+            error::parse("0x2", "package id")?,
+            error::parse("balance", "Move identifier")?,
+            error::parse("destroy_zero", "Move identifier")?,
             vec![zero_type],
         ),
         vec![zero_balance],
@@ -194,9 +789,9 @@ pub fn swap(
     debug_momentum("[swap] step 4: from_balance -> output_coin");
     let output_coin = tx.move_call(
         Function::new(
-            "0x2".parse()?,
-            "coin".parse()?,
-            "from_balance".parse()?,
+            error::parse("0x2", "package id")?,
+            error::parse("coin", "Move identifier")?,
+            error::parse("from_balance", "Move identifier")?,
             vec![output_type.clone()],
         ),
         vec![output_balance],
@@ -206,9 +801,9 @@ pub fn swap(
     debug_momentum("[swap] step 5: swap_receipt_debts");
     let receipt_debts_result = tx.move_call(
         Function::new(
-            MOMENTUM_TRADE_PACKAGE.parse()?,
-            "trade".parse()?,
-            "swap_receipt_debts".parse()?,
+            trade_package,
+            error::parse("trade", "Move identifier")?,
+            error::parse("swap_receipt_debts", "Move identifier")?,
             vec![],
         ),
         vec![flash_swap_r3],
@@ -216,10 +811,10 @@ pub fn swap(
 
     let receipt_debt_a = receipt_debts_result
         .nested(0)
-        .expect("swap_receipt_debts result index 0 missing");
+        .ok_or_else(|| QuantError::Build("swap_receipt_debts result index 0 missing".to_string()))?;
     let receipt_debt_b = receipt_debts_result
         .nested(1)
-        .expect("swap_receipt_debts result index 1 missing");
+        .ok_or_else(|| QuantError::Build("swap_receipt_debts result index 1 missing".to_string()))?;
 
     let repay_debt = if direction {
         receipt_debt_a
@@ -238,25 +833,25 @@ pub fn swap(
         a_token_type.clone()
     };
 
-    // 6. Split coin for repayment.
+    // 6. Split coin for repayment (the receipt debt, in both modes).
     debug_momentum("[swap] step 6: coin::split for repayment");
     let repay_from_split = tx.move_call(
         Function::new(
-            "0x2".parse()?,
-            "coin".parse()?,
-            "split".parse()?,
+            error::parse("0x2", "package id")?,
+            error::parse("coin", "Move identifier")?,
+            error::parse("split", "Move identifier")?,
             vec![repay_type.clone()],
         ),
-        vec![split_coin, repay_debt],
+        vec![input_coin, repay_debt],
     );
 
     // 7. Convert repay coin to balance.
     debug_momentum("[swap] step 7: coin::into_balance for repay");
     let balance1 = tx.move_call(
         Function::new(
-            "0x2".parse()?,
-            "coin".parse()?,
-            "into_balance".parse()?,
+            error::parse("0x2", "package id")?,
+            error::parse("coin", "Move identifier")?,
+            error::parse("into_balance", "Move identifier")?,
             vec![repay_type.clone()],
         ),
         vec![repay_from_split],
@@ -266,9 +861,9 @@ pub fn swap(
     debug_momentum("[swap] step 8: coin::zero");
     let zero_coin = tx.move_call(
         Function::new(
-            "0x2".parse()?,
-            "coin".parse()?,
-            "zero".parse()?,
+            error::parse("0x2", "package id")?,
+            error::parse("coin", "Move identifier")?,
+            error::parse("zero", "Move identifier")?,
             vec![zero_coin_type.clone()],
         ),
         vec![],
@@ -278,9 +873,9 @@ pub fn swap(
     debug_momentum("[swap] step 9: coin::into_balance for zero_coin");
     let balance2 = tx.move_call(
         Function::new(
-            "0x2".parse()?,
-            "coin".parse()?,
-            "into_balance".parse()?,
+            error::parse("0x2", "package id")?,
+            error::parse("coin", "Move identifier")?,
+            error::parse("into_balance", "Move identifier")?,
             vec![zero_coin_type],
         ),
         vec![zero_coin],
@@ -296,49 +891,57 @@ pub fn swap(
 
     tx.move_call(
         Function::new(
-            MOMENTUM_TRADE_PACKAGE.parse()?,
-            "trade".parse()?,
-            "repay_flash_swap".parse()?,
+            trade_package,
+            error::parse("trade", "Move identifier")?,
+            error::parse("repay_flash_swap", "Move identifier")?,
             vec![a_token_type.clone(), b_token_type.clone()],
         ),
         vec![pool, flash_swap_r3, repay_balance_a, repay_balance_b, versioned_object],
     );
 
-    // 11. Slippage check.
-    let slippage_limit: u128 = if direction {
-        0u128
-    } else {
-        u64::MAX as u128
-    };
+    Ok(output_coin)
+}
 
-    debug_momentum(&format!(
-        "[swap] step 11: assert_slippage, slippage_limit={slippage_limit}"
-    ));
+fn debug_momentum(msg: &str) {
+    if DEBUG_MOMENTUM {
+        eprintln!("{msg}");
+    }
+}
 
-    let slippage_limit_arg = tx.input(Serialized(&slippage_limit));
-    let direction_arg_2 = tx.input(Serialized(&direction));
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    tx.move_call(
-        Function::new(
-            MOMENTUM_SLIPPAGE_PACKAGE.parse()?,
-            "slippage_check".parse()?,
-            "assert_slippage".parse()?,
-            vec![a_token_type, b_token_type],
-        ),
-        vec![pool, slippage_limit_arg, direction_arg_2],
-    );
+    const PACKAGE: &str = "0x70285592c97965e811e0c6f98dccc3a9c2b4ad854b3594faab9597ada267b860";
 
-    // 12. Transfer output coin + remaining split coin back to sender.
-    debug_momentum("[swap] step 12: transfer_objects back to sender");
-    let sender_arg = tx.input(Serialized(&sender));
-    tx.transfer_objects(vec![output_coin, split_coin], sender_arg);
+    #[test]
+    fn parse_pool_coin_types_splits_the_type_parameters() {
+        let pool_type = format!("{PACKAGE}::pool::Pool<0x2::sui::SUI, 0xdba3::usdc::USDC>");
+        assert_eq!(
+            parse_pool_coin_types(&pool_type).unwrap(),
+            ("0x2::sui::SUI", "0xdba3::usdc::USDC")
+        );
+    }
 
-    debug_momentum("[swap] end");
-    Ok(())
-}
+    #[test]
+    fn parse_pool_coin_types_keeps_generic_coin_types() {
+        let pool_type = format!("{PACKAGE}::pool::Pool<0x1::lp::LP<0x2::sui::SUI, 0x3::a::A>, 0x2::sui::SUI>");
+        assert_eq!(
+            parse_pool_coin_types(&pool_type).unwrap(),
+            ("0x1::lp::LP<0x2::sui::SUI, 0x3::a::A>", "0x2::sui::SUI")
+        );
+    }
 
-fn debug_momentum(msg: &str) {
-    if DEBUG_MOMENTUM {
-        eprintln!("{msg}");
+    #[test]
+    fn parse_pool_coin_types_rejects_other_types() {
+        for pool_type in [
+            "0x2::coin::Coin<0x2::sui::SUI>".to_string(),
+            format!("{PACKAGE}::pool::Pool<0x2::sui::SUI>"),
+            format!("{PACKAGE}::pool::Pool<0x2::sui::SUI, 0x3::a::A, 0x3::b::B>"),
+            format!("{PACKAGE}::pool::Pool<0x2::sui::SUI, >"),
+            format!("{PACKAGE}::pool::Pool<0x1::lp::LP<0x2::sui::SUI, 0x3::a::A>"),
+        ] {
+            assert!(parse_pool_coin_types(&pool_type).is_err(), "{pool_type}");
+        }
     }
 }
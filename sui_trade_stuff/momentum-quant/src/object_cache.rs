@@ -0,0 +1,358 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sui_rpc::proto::sui::rpc::v2::{ExecutedTransaction, Object};
+use sui_rpc::proto::sui::rpc::v2::owner::OwnerKind;
+use sui_sdk_types::{Address, Digest, TypeTag};
+use sui_transaction_builder::unresolved::Input;
+
+use crate::cache_file;
+use crate::coins::OwnedCoin;
+use crate::error::{self, QuantError};
+use crate::momentum;
+
+/// Enable / disable debug logs inside object_cache module.
+const DEBUG_OBJECT_CACHE: bool = false;
+
+/// Object cache file.
+const OBJECT_CACHE_FILE: &str = "objects.json";
+
+/// What a transaction input needs to know about an object.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum CachedObject {
+    /// A shared object's initial version never changes.
+    Shared { initial_shared_version: u64 },
+    /// Latest version and digest of an owned object, as of the last
+    /// transaction this program saw touch it.
+    Owned {
+        version: u64,
+        digest: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        coin: Option<CachedCoin>,
+    },
+}
+
+/// Owner, type and balance of a cached coin.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CachedCoin {
+    pub owner: String,
+    /// Full object type, `0x2::coin::Coin<T>`.
+    pub object_type: String,
+    pub balance: u64,
+}
+
+impl CachedCoin {
+    fn is_of_type(&self, coin_type: &TypeTag) -> bool {
+        momentum::coin_object_type(&self.object_type).is_ok_and(|own_type| own_type == *coin_type)
+    }
+}
+
+/// Cached objects of one network.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct NetworkObjects {
+    #[serde(default)]
+    objects: BTreeMap<String, CachedObject>,
+    /// `owner/object type` pairs whose coins were listed: every coin of
+    /// them is in `objects`.
+    #[serde(default)]
+    coin_listings: BTreeSet<String>,
+}
+
+/// Object inputs of one network, saved between runs so swaps skip the
+/// `GetObject` reads of objects they already know: the clock, the versioned
+/// object and the `--token` coin. Gas and input coins are listed once per
+/// owner and type, then follow the balances written by each transaction.
+///
+/// Owned entries are only correct while no other client uses the object;
+/// they follow the effects of every transaction executed here.
+#[derive(Debug)]
+pub struct ObjectCache {
+    path: Option<PathBuf>,
+    network: String,
+    networks: BTreeMap<String, NetworkObjects>,
+    /// Shared objects handed out as inputs since loading.
+    used_shared: BTreeSet<Address>,
+}
+
+impl ObjectCache {
+    /// Load the cache of `network`; a missing or unreadable file is empty.
+    pub fn load(network: &str) -> ObjectCache {
+        let path = cache_file::cache_path(OBJECT_CACHE_FILE);
+        let networks = cache_file::read(path.as_deref());
+        ObjectCache {
+            path,
+            network: network.to_string(),
+            networks,
+            used_shared: BTreeSet::new(),
+        }
+    }
+
+    pub fn get(&self, object_id: &Address) -> Option<&CachedObject> {
+        self.networks.get(&self.network)?.objects.get(&object_id.to_string())
+    }
+
+    pub fn insert(&mut self, object_id: &Address, object: CachedObject) {
+        debug_object_cache(&format!("[ObjectCache::insert] {object_id}: {object:?}"));
+        self.networks
+            .entry(self.network.clone())
+            .or_default()
+            .objects
+            .insert(object_id.to_string(), object);
+    }
+
+    pub fn remove(&mut self, object_id: &Address) {
+        if let Some(network) = self.networks.get_mut(&self.network) {
+            network.objects.remove(&object_id.to_string());
+        }
+    }
+
+    /// Forget every owned object, e.g. after a failure that may come from a
+    /// version changed by another client.
+    pub fn clear_owned(&mut self) {
+        if let Some(network) = self.networks.get_mut(&self.network) {
+            network.objects.retain(|_, object| matches!(object, CachedObject::Shared { .. }));
+            network.coin_listings.clear();
+        }
+    }
+
+    /// Coins of `coin_object_type` owned by `owner`, `None` until a listing
+    /// of them was cached with `insert_coins`.
+    pub fn coins(&self, owner: &Address, coin_object_type: &str) -> Result<Option<Vec<OwnedCoin>>, QuantError> {
+        let Some(network) = self.networks.get(&self.network) else {
+            return Ok(None);
+        };
+        if !network.coin_listings.contains(&coin_listing(owner, coin_object_type)) {
+            return Ok(None);
+        }
+
+        let coin_type = momentum::coin_object_type(coin_object_type)?;
+        let owner = owner.to_string();
+        let mut coins = Vec::new();
+        for (object_id, object) in &network.objects {
+            let CachedObject::Owned { version, digest, coin: Some(coin) } = object else {
+                continue;
+            };
+            if coin.owner != owner || !coin.is_of_type(&coin_type) {
+                continue;
+            }
+            coins.push(OwnedCoin {
+                object_id: error::parse(object_id, "object id")?,
+                version: *version,
+                digest: error::parse(digest, "object digest")?,
+                balance: coin.balance,
+                object_type: coin.object_type.clone(),
+            });
+        }
+        Ok(Some(coins))
+    }
+
+    /// Cache a full listing of the coins of `coin_object_type` owned by
+    /// `owner`, replacing the ones cached before.
+    pub fn insert_coins(&mut self, owner: &Address, coin_object_type: &str, coins: &[OwnedCoin]) {
+        let owner_string = owner.to_string();
+        let coin_type = momentum::coin_object_type(coin_object_type).ok();
+        let network = self.networks.entry(self.network.clone()).or_default();
+        network.objects.retain(|_, object| match (object, &coin_type) {
+            (CachedObject::Owned { coin: Some(coin), .. }, Some(coin_type)) => {
+                coin.owner != owner_string || !coin.is_of_type(coin_type)
+            }
+            _ => true,
+        });
+        network.coin_listings.insert(coin_listing(owner, coin_object_type));
+
+        for coin in coins {
+            self.insert(
+                &coin.object_id,
+                CachedObject::Owned {
+                    version: coin.version,
+                    digest: coin.digest.to_string(),
+                    coin: Some(CachedCoin {
+                        owner: owner_string.clone(),
+                        object_type: coin.object_type.clone(),
+                        balance: coin.balance,
+                    }),
+                },
+            );
+        }
+    }
+
+    /// Shared objects handed out as inputs since loading, e.g. to check
+    /// them after the node rejected an input.
+    pub fn used_shared(&self) -> Vec<Address> {
+        self.used_shared.iter().copied().collect()
+    }
+
+    /// Input of a cached object, `None` if it is not cached.
+    pub fn input(&mut self, object_id: &Address) -> Result<Option<Input>, QuantError> {
+        let input = match self.get(object_id) {
+            Some(&CachedObject::Shared { initial_shared_version }) => {
+                let input = Input::by_id(*object_id)
+                    .with_shared_kind()
+                    .with_initial_shared_version(initial_shared_version);
+                self.used_shared.insert(*object_id);
+                input
+            }
+            Some(CachedObject::Owned { version, digest, .. }) => Input::by_id(*object_id)
+                .with_owned_kind()
+                .with_version(*version)
+                .with_digest(error::parse::<Digest>(digest, "object digest")?),
+            None => return Ok(None),
+        };
+        Ok(Some(input))
+    }
+
+    /// Move cached owned objects to the versions written by `transaction`,
+    /// and drop the ones it deleted, wrapped or gave away. Coins it wrote to
+    /// an address, created ones included, are cached with their new balance
+    /// from the transaction's output objects.
+    pub fn apply_transaction(&mut self, transaction: &ExecutedTransaction) {
+        let Some(effects) = &transaction.effects else {
+            return;
+        };
+        for changed in &effects.changed_objects {
+            let Some(object_id) = changed
+                .object_id
+                .as_deref()
+                .and_then(|id| id.parse::<Address>().ok())
+            else {
+                continue;
+            };
+            let owner = changed
+                .output_owner
+                .as_ref()
+                .filter(|owner| owner.kind() == OwnerKind::Address)
+                .and_then(|owner| owner.address.clone());
+            let coin = owner.and_then(|owner| {
+                let object = output_object(transaction, &object_id, changed.output_version?)?;
+                let object_type = object.object_type.clone()?;
+                momentum::coin_object_type(&object_type).ok()?;
+                Some(CachedCoin {
+                    owner,
+                    object_type,
+                    balance: object.balance?,
+                })
+            });
+
+            let cached = matches!(self.get(&object_id), Some(CachedObject::Owned { .. }));
+            if !cached && coin.is_none() {
+                continue;
+            }
+            match (changed.output_owner.as_ref(), changed.output_version, changed.output_digest.clone()) {
+                (Some(owner), Some(version), Some(digest)) if owner.kind() == OwnerKind::Address => {
+                    self.insert(&object_id, CachedObject::Owned { version, digest, coin })
+                }
+                _ => {
+                    debug_object_cache(&format!("[ObjectCache::apply_transaction] dropping {object_id}"));
+                    self.remove(&object_id);
+                }
+            }
+        }
+    }
+
+    /// Write the cache back.
+    pub fn save(&self) {
+        cache_file::write(self.path.as_deref(), &self.networks);
+    }
+}
+
+/// Output version of `object_id` among the objects of `transaction`.
+fn output_object<'a>(transaction: &'a ExecutedTransaction, object_id: &Address, version: u64) -> Option<&'a Object> {
+    transaction.objects.as_ref()?.objects.iter().find(|object| {
+        object.version == Some(version)
+            && object.object_id.as_deref().and_then(|id| id.parse::<Address>().ok()) == Some(*object_id)
+    })
+}
+
+/// Key of the coin listing of `owner` and `coin_object_type`.
+fn coin_listing(owner: &Address, coin_object_type: &str) -> String {
+    format!("{owner}/{coin_object_type}")
+}
+
+fn debug_object_cache(msg: &str) {
+    if DEBUG_OBJECT_CACHE {
+        eprintln!("{msg}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_rpc::proto::sui::rpc::v2::{ChangedObject, ObjectSet, Owner, TransactionEffects};
+
+    use super::*;
+
+    const OWNER: &str = "0x1234";
+    const SUI: &str = "0x2::coin::Coin<0x2::sui::SUI>";
+
+    fn cache() -> ObjectCache {
+        ObjectCache {
+            path: None,
+            network: "test".to_string(),
+            networks: BTreeMap::new(),
+            used_shared: BTreeSet::new(),
+        }
+    }
+
+    fn coin(object_id: &str, balance: u64) -> OwnedCoin {
+        OwnedCoin {
+            object_id: object_id.parse().unwrap(),
+            version: 1,
+            digest: Digest::ZERO,
+            balance,
+            object_type: SUI.to_string(),
+        }
+    }
+
+    /// Transaction writing `object_id` at version 2 to `OWNER` with `balance`.
+    fn transaction(object_id: &str, balance: u64) -> ExecutedTransaction {
+        let mut owner = Owner::default();
+        owner.set_kind(OwnerKind::Address);
+        owner.address = Some(OWNER.parse::<Address>().unwrap().to_string());
+        let mut changed = ChangedObject::default();
+        changed.object_id = Some(object_id.to_string());
+        changed.output_version = Some(2);
+        changed.output_digest = Some(Digest::ZERO.to_string());
+        changed.output_owner = Some(owner);
+        let mut effects = TransactionEffects::default();
+        effects.changed_objects = vec![changed];
+
+        let mut object = Object::default();
+        object.object_id = Some(object_id.to_string());
+        object.version = Some(2);
+        object.object_type = Some(SUI.to_string());
+        object.balance = Some(balance);
+        let mut objects = ObjectSet::default();
+        objects.objects = vec![object];
+
+        let mut transaction = ExecutedTransaction::default();
+        transaction.effects = Some(effects);
+        transaction.objects = Some(objects);
+        transaction
+    }
+
+    #[test]
+    fn coins_follow_the_executed_transaction() {
+        let owner: Address = OWNER.parse().unwrap();
+        let mut cache = cache();
+        assert!(cache.coins(&owner, SUI).unwrap().is_none());
+
+        cache.insert_coins(&owner, SUI, &[coin("0xa", 100)]);
+        cache.apply_transaction(&transaction("0xa", 60));
+        cache.apply_transaction(&transaction("0xb", 5));
+
+        let mut coins = cache.coins(&owner, SUI).unwrap().unwrap();
+        coins.sort_by_key(|coin| coin.balance);
+        let balances: Vec<(u64, u64)> = coins.iter().map(|coin| (coin.version, coin.balance)).collect();
+        assert_eq!(balances, vec![(2, 5), (2, 60)]);
+    }
+
+    #[test]
+    fn clear_owned_forgets_coin_listings() {
+        let owner: Address = OWNER.parse().unwrap();
+        let mut cache = cache();
+        cache.insert_coins(&owner, SUI, &[coin("0xa", 100)]);
+        cache.clear_owned();
+        assert!(cache.coins(&owner, SUI).unwrap().is_none());
+    }
+}
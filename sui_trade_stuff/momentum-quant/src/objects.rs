@@ -0,0 +1,150 @@
+use std::io;
+
+use prost_types::FieldMask;
+use sui_rpc::Client;
+use sui_rpc::proto::google::rpc::Status;
+use sui_rpc::proto::sui::rpc::v2::get_object_result;
+use sui_rpc::proto::sui::rpc::v2::{BatchGetObjectsRequest, GetObjectRequest, Object};
+use sui_sdk_types::Address;
+use tokio::task::JoinSet;
+
+use crate::error::QuantError;
+
+/// Enable / disable debug logs inside objects module.
+const DEBUG_OBJECTS: bool = false;
+
+/// Fields needed to use an object as a transaction input.
+pub const OBJECT_FIELDS: &[&str] = &["object_id", "version", "digest", "owner", "object_type"];
+
+/// Input fields plus the JSON contents, for pools (sqrt_price, liquidity, ...).
+pub const POOL_FIELDS: &[&str] = &["object_id", "version", "digest", "owner", "object_type", "json"];
+
+/// Read one object with `fields`.
+pub async fn fetch_object(
+    client: &mut Client,
+    object_id: Address,
+    fields: &[&str],
+) -> Result<Object, QuantError> {
+    let mut ledger_client = client.ledger_client();
+
+    let mut request = GetObjectRequest::new(&object_id);
+    request.read_mask = Some(read_mask(fields));
+
+    let response = ledger_client
+        .get_object(request)
+        .await
+        .map_err(|status| QuantError::object_rpc(object_id, status))?
+        .into_inner();
+    response
+        .object
+        .ok_or_else(|| QuantError::ObjectNotFound(object_id.to_string()))
+}
+
+/// Read every `(object_id, fields)` with one `LedgerService.BatchGetObjects`
+/// call, returning the objects in request order.
+///
+/// A batch takes one read mask, the union of all `fields`. If the batch
+/// call itself fails, the objects are read with concurrent `GetObject`
+/// calls, each with its own fields.
+pub async fn fetch_objects(
+    client: &mut Client,
+    requests: &[(Address, &'static [&'static str])],
+) -> Result<Vec<Object>, QuantError> {
+    if requests.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    match batch_get_objects(client, requests).await {
+        Ok(objects) => Ok(objects),
+        Err(QuantError::Rpc(status)) => {
+            debug_objects(&format!(
+                "[fetch_objects] batch failed ({:?}: {}), falling back to single reads",
+                status.code(),
+                status.message()
+            ));
+            fetch_objects_concurrently(client, requests).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn batch_get_objects(
+    client: &mut Client,
+    requests: &[(Address, &'static [&'static str])],
+) -> Result<Vec<Object>, QuantError> {
+    let mut ledger_client = client.ledger_client();
+
+    let mut fields: Vec<&str> = Vec::new();
+    for field in requests.iter().flat_map(|(_, fields)| fields.iter()) {
+        if !fields.contains(field) {
+            fields.push(field);
+        }
+    }
+
+    let mut request = BatchGetObjectsRequest::default();
+    request.requests = requests
+        .iter()
+        .map(|(object_id, _)| GetObjectRequest::new(object_id))
+        .collect();
+    request.read_mask = Some(read_mask(&fields));
+
+    debug_objects(&format!("[batch_get_objects] {} objects", requests.len()));
+    let response = ledger_client.batch_get_objects(request).await?.into_inner();
+    if response.objects.len() != requests.len() {
+        return Err(QuantError::InvalidResponse(format!(
+            "BatchGetObjects returned {} results for {} objects",
+            response.objects.len(),
+            requests.len()
+        )));
+    }
+
+    response
+        .objects
+        .into_iter()
+        .zip(requests)
+        .map(|(result, (object_id, _))| match result.result {
+            Some(get_object_result::Result::Object(object)) => Ok(object),
+            Some(get_object_result::Result::Error(status)) => Err(object_error(object_id, status)),
+            _ => Err(QuantError::ObjectNotFound(object_id.to_string())),
+        })
+        .collect()
+}
+
+async fn fetch_objects_concurrently(
+    client: &mut Client,
+    requests: &[(Address, &'static [&'static str])],
+) -> Result<Vec<Object>, QuantError> {
+    let mut tasks = JoinSet::new();
+    for (index, (object_id, fields)) in requests.iter().enumerate() {
+        let mut client = client.clone();
+        let (object_id, fields) = (*object_id, *fields);
+        tasks.spawn(async move { (index, fetch_object(&mut client, object_id, fields).await) });
+    }
+
+    let mut objects: Vec<Option<Object>> = vec![None; requests.len()];
+    while let Some(joined) = tasks.join_next().await {
+        let (index, object) = joined.map_err(io::Error::from)?;
+        objects[index] = Some(object?);
+    }
+    Ok(objects.into_iter().flatten().collect())
+}
+
+/// Per-object error of a batch, `ObjectNotFound` for a missing object.
+fn object_error(object_id: &Address, status: Status) -> QuantError {
+    QuantError::object_rpc(
+        object_id,
+        tonic::Status::new(tonic::Code::from(status.code), status.message),
+    )
+}
+
+fn read_mask(fields: &[&str]) -> FieldMask {
+    FieldMask {
+        paths: fields.iter().map(|field| field.to_string()).collect(),
+    }
+}
+
+fn debug_objects(msg: &str) {
+    if DEBUG_OBJECTS {
+        eprintln!("{msg}");
+    }
+}
@@ -0,0 +1,343 @@
+use sui_rpc::proto::sui::rpc::v2::execution_error::ErrorDetails;
+use sui_rpc::proto::sui::rpc::v2::{ExecuteTransactionResponse, ExecutedTransaction};
+use sui_sdk_types::{Address, Argument, Command, Transaction, TransactionKind};
+
+use crate::config::{AbortReasonConfig, NetworkConfig};
+use crate::error::QuantError;
+use crate::simulate::GasUsed;
+
+/// Enable / disable debug logs inside outcome module.
+const DEBUG_OUTCOME: bool = false;
+
+/// Package of the Sui framework modules (`0x2::balance`, `0x2::coin`, ...).
+const SUI_FRAMEWORK: &str = "0x2";
+
+/// Reason given to a failed minimum output check.
+const SLIPPAGE_EXCEEDED: &str = "slippage exceeded";
+
+/// Package an abort reason belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AbortPackage {
+    Framework,
+    /// The configured Momentum `trade` package.
+    Trade,
+    /// The configured Momentum `slippage` package.
+    Slippage,
+}
+
+/// Readable reasons of Move aborts as `(package, module, code, reason)`,
+/// where `None` matches every code of the module.
+///
+/// `slippage_check` and `version` only abort on a failed slippage or
+/// version check. The codes of the trade package's `trade` and `pool`
+/// modules come from the network's `abort_reasons`, and named (clever)
+/// errors read their constant name.
+const ABORT_REASONS: &[(AbortPackage, &str, Option<u64>, &str)] = &[
+    (AbortPackage::Slippage, "slippage_check", None, SLIPPAGE_EXCEEDED),
+    (AbortPackage::Trade, "version", None, "unsupported Momentum package version"),
+    (AbortPackage::Framework, "balance", Some(0), "flash swap left a non-zero balance"),
+    (AbortPackage::Framework, "balance", Some(1), "balance overflow"),
+    (AbortPackage::Framework, "balance", Some(2), "insufficient balance"),
+];
+
+/// What the failures of one transaction mean: the Momentum packages its
+/// aborts may come from and the command checking its minimum output.
+#[derive(Clone, Debug, Default)]
+pub struct AbortContext {
+    /// Configured reasons of the trade package's codes.
+    trade_reasons: Vec<AbortReasonConfig>,
+    trade_packages: Vec<Address>,
+    slippage_packages: Vec<Address>,
+    /// Whether the original ids are configured; without them an upgraded
+    /// package is only known by its module names.
+    originals_known: bool,
+    min_output_command: Option<u64>,
+}
+
+impl AbortContext {
+    pub fn new(network: &NetworkConfig, tx: &Transaction) -> AbortContext {
+        let packages = &network.packages;
+        let ids = |current: &str, original: &Option<String>| -> Vec<Address> {
+            std::iter::once(current)
+                .chain(original.as_deref())
+                .filter_map(|id| id.parse().ok())
+                .collect()
+        };
+        AbortContext {
+            trade_reasons: network.abort_reasons.clone(),
+            trade_packages: ids(&packages.trade, &packages.trade_original),
+            slippage_packages: ids(&packages.slippage, &packages.slippage_original),
+            originals_known: packages.trade_original.is_some() && packages.slippage_original.is_some(),
+            min_output_command: min_output_command(tx),
+        }
+    }
+
+    /// Package of an abort location. Abort locations name the original
+    /// package id, so an unknown package falls back to the Momentum module
+    /// names unless the original ids are configured.
+    fn package(&self, package: &str, module: &str) -> Option<AbortPackage> {
+        let package = package.parse::<Address>().ok()?;
+        if SUI_FRAMEWORK.parse::<Address>().ok() == Some(package) {
+            Some(AbortPackage::Framework)
+        } else if self.trade_packages.contains(&package) {
+            Some(AbortPackage::Trade)
+        } else if self.slippage_packages.contains(&package) {
+            Some(AbortPackage::Slippage)
+        } else if self.originals_known {
+            None
+        } else if self.trade_reasons.iter().any(|reason| reason.module == module) {
+            Some(AbortPackage::Trade)
+        } else {
+            ABORT_REASONS
+                .iter()
+                .find(|(reason_package, reason_module, _, _)| {
+                    *reason_package != AbortPackage::Framework && *reason_module == module
+                })
+                .map(|(reason_package, _, _, _)| *reason_package)
+        }
+    }
+
+    fn abort_reason(&self, package: &str, module: &str, code: u64) -> Option<String> {
+        let package = self.package(package, module)?;
+        let configured = self
+            .trade_reasons
+            .iter()
+            .filter(|_| package == AbortPackage::Trade)
+            .find(|reason| reason.module == module && reason.code == code)
+            .map(|reason| reason.reason.clone());
+        configured.or_else(|| {
+            ABORT_REASONS
+                .iter()
+                .find(|(reason_package, reason_module, reason_code, _)| {
+                    *reason_package == package
+                        && *reason_module == module
+                        && reason_code.is_none_or(|reason_code| reason_code == code)
+                })
+                .map(|(_, _, _, reason)| reason.to_string())
+        })
+    }
+}
+
+/// Index of the minimum output check in `tx`: a `SplitCoins` of the output
+/// coin whose result the next command merges straight back (see
+/// `momentum::assert_min_amount_out`). It fails with an insufficient coin
+/// balance rather than a Move abort.
+fn min_output_command(tx: &Transaction) -> Option<u64> {
+    let TransactionKind::ProgrammableTransaction(ptb) = &tx.kind else {
+        return None;
+    };
+    ptb.commands
+        .windows(2)
+        .enumerate()
+        .find_map(|(i, commands)| match commands {
+            [Command::SplitCoins(split), Command::MergeCoins(merge)]
+                if merge.coin == split.coin && merge.coins_to_merge == [Argument::Result(i as u16)] =>
+            {
+                Some(i as u64)
+            }
+            _ => None,
+        })
+}
+
+/// Decoded status of an executed (or simulated) transaction.
+#[derive(Clone, Debug)]
+pub struct ExecutionOutcome {
+    pub digest: Option<String>,
+    pub success: bool,
+    /// Execution error description, if the transaction failed.
+    pub error: Option<String>,
+    /// Index of the command that failed.
+    pub failed_command: Option<u64>,
+    pub abort: Option<MoveAbortInfo>,
+    /// Whether the failed command is the minimum output check.
+    pub slippage_exceeded: bool,
+    pub gas_used: GasUsed,
+}
+
+/// Location and code of a Move abort.
+#[derive(Clone, Debug)]
+pub struct MoveAbortInfo {
+    pub package: String,
+    pub module: String,
+    pub function: String,
+    pub code: u64,
+    /// Readable reason, if the package, module and code are known or the
+    /// abort is a named (clever) error.
+    pub reason: Option<String>,
+}
+
+impl ExecutionOutcome {
+    pub fn from_response(
+        response: &ExecuteTransactionResponse,
+        context: &AbortContext,
+    ) -> Result<ExecutionOutcome, QuantError> {
+        let transaction = response
+            .transaction
+            .as_ref()
+            .ok_or_else(|| QuantError::invalid_response("Execution returned no transaction"))?;
+        ExecutionOutcome::from_executed(transaction, context)
+    }
+
+    pub fn from_executed(
+        transaction: &ExecutedTransaction,
+        context: &AbortContext,
+    ) -> Result<ExecutionOutcome, QuantError> {
+        let effects = transaction
+            .effects
+            .as_ref()
+            .ok_or_else(|| QuantError::invalid_response("Transaction returned no effects"))?;
+        let status = effects
+            .status
+            .as_ref()
+            .ok_or_else(|| QuantError::invalid_response("Transaction effects without status"))?;
+        let error = status.error.as_ref();
+
+        let abort = error
+            .and_then(|error| match &error.error_details {
+                Some(ErrorDetails::Abort(abort)) => Some(abort),
+                _ => None,
+            })
+            .map(|abort| {
+                let location = abort.location.clone().unwrap_or_default();
+                let package = location.package.unwrap_or_default();
+                let module = location.module.unwrap_or_default();
+                let code = abort.abort_code.unwrap_or_default();
+                let constant_name = abort
+                    .clever_error
+                    .as_ref()
+                    .and_then(|clever_error| clever_error.constant_name.as_deref());
+                MoveAbortInfo {
+                    reason: context
+                        .abort_reason(&package, &module, code)
+                        .or_else(|| constant_name.map(constant_reason)),
+                    package,
+                    module,
+                    function: location.function_name.unwrap_or_default(),
+                    code,
+                }
+            });
+        let failed_command = error.and_then(|error| error.command);
+
+        let outcome = ExecutionOutcome {
+            digest: transaction.digest.clone(),
+            success: status.success.unwrap_or(false),
+            error: error.and_then(|error| error.description.clone()),
+            failed_command,
+            slippage_exceeded: abort.is_none()
+                && failed_command.is_some()
+                && failed_command == context.min_output_command,
+            abort,
+            gas_used: effects
+                .gas_used
+                .as_ref()
+                .map(GasUsed::from_proto)
+                .unwrap_or_default(),
+        };
+        debug_outcome(&format!("[ExecutionOutcome::from_executed] {outcome:?}"));
+        Ok(outcome)
+    }
+
+    /// Fail with the Move abort or execution error if the transaction failed.
+    pub fn ensure_success(&self) -> Result<(), QuantError> {
+        if self.success {
+            return Ok(());
+        }
+        if self.slippage_exceeded {
+            return Err(QuantError::SlippageExceeded {
+                command: self.failed_command,
+            });
+        }
+        Err(match &self.abort {
+            Some(abort) => QuantError::MoveAbort {
+                abort: abort.clone(),
+                command: self.failed_command,
+            },
+            None => QuantError::ExecutionFailed {
+                command: self.failed_command,
+                message: self.error.clone().unwrap_or_else(|| "unknown error".to_string()),
+            },
+        })
+    }
+
+    pub fn print(&self) {
+        if let Some(digest) = &self.digest {
+            status!("Digest: {digest}");
+        }
+        status!("Status: {}", if self.success { "success" } else { "failure" });
+        if let Some(error) = &self.error {
+            status!("Error: {error}");
+        }
+        if let Some(command) = self.failed_command {
+            if self.slippage_exceeded {
+                status!("Failed command: {command} (minimum output check, {SLIPPAGE_EXCEEDED})");
+            } else {
+                status!("Failed command: {command}");
+            }
+        }
+        if let Some(abort) = &self.abort {
+            status!("Move abort: {abort}");
+        }
+        status!(
+            "Gas used: computation={} storage={} rebate={} non_refundable={} (net {})",
+            self.gas_used.computation_cost,
+            self.gas_used.storage_cost,
+            self.gas_used.storage_rebate,
+            self.gas_used.non_refundable_storage_fee,
+            self.gas_used.net_cost()
+        );
+    }
+}
+
+impl std::fmt::Display for MoveAbortInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "code {} in {}::{}::{}",
+            self.code, self.package, self.module, self.function
+        )?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({reason})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Readable form of a Move error constant: `EPoolPaused` and
+/// `E_POOL_PAUSED` both read "pool paused".
+fn constant_reason(constant_name: &str) -> String {
+    let name = constant_name
+        .strip_prefix("E_")
+        .or_else(|| constant_name.strip_prefix('E'))
+        .unwrap_or(constant_name);
+    let mut reason = String::new();
+    for c in name.chars() {
+        if c == '_' || (c.is_ascii_uppercase() && !name.contains('_')) {
+            if !reason.is_empty() && !reason.ends_with(' ') {
+                reason.push(' ');
+            }
+            if c == '_' {
+                continue;
+            }
+        }
+        reason.push(c.to_ascii_lowercase());
+    }
+    reason
+}
+
+fn debug_outcome(msg: &str) {
+    if DEBUG_OUTCOME {
+        eprintln!("{msg}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_reason_splits_error_names() {
+        assert_eq!(constant_reason("EPoolPaused"), "pool paused");
+        assert_eq!(constant_reason("E_POOL_PAUSED"), "pool paused");
+        assert_eq!(constant_reason("EInvalidSqrtPriceLimit"), "invalid sqrt price limit");
+    }
+}
@@ -0,0 +1,473 @@
+use std::collections::BTreeMap;
+
+use prost_types::{value, FieldMask, Value};
+use sui_rpc::Client;
+use sui_rpc::proto::sui::rpc::v2::{GetObjectRequest, ListDynamicFieldsRequest};
+use sui_sdk_types::Address;
+
+use crate::error::{self, QuantError};
+
+/// Enable / disable debug logs inside quote module.
+const DEBUG_QUOTE: bool = false;
+
+/// Fields of the Momentum `Pool` JSON used by the quote engine.
+const FIELD_SQRT_PRICE: &str = "sqrt_price";
+const FIELD_LIQUIDITY: &str = "liquidity";
+const FIELD_TICK_INDEX: &str = "tick_index";
+const FIELD_TICK_SPACING: &str = "tick_spacing";
+const FIELD_FEE_RATE: &str = "swap_fee_rate";
+const FIELD_TICKS: &str = "ticks";
+
+/// Fee rates are expressed in millionths.
+const FEE_RATE_DENOMINATOR: f64 = 1_000_000.0;
+
+/// 2^64, the Q64.64 scaling factor.
+const Q64: f64 = 18_446_744_073_709_551_616.0;
+
+/// Page size used when listing the tick table.
+const TICKS_PAGE_SIZE: u32 = 1000;
+
+/// On-chain state of a Momentum pool needed to simulate swaps.
+#[derive(Clone, Debug)]
+pub struct PoolState {
+    /// Current sqrt price, Q64.64.
+    pub sqrt_price: u128,
+    /// Active liquidity.
+    pub liquidity: u128,
+    /// Current tick index.
+    pub tick_index: i32,
+    pub tick_spacing: u32,
+    /// Swap fee rate in millionths.
+    pub fee_rate: u64,
+    /// Net liquidity of every initialized tick.
+    pub ticks: BTreeMap<i32, i128>,
+}
+
+/// Result of a locally simulated swap.
+#[derive(Clone, Debug)]
+pub struct Quote {
+    /// Input actually consumed, fee included.
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Fee paid in the input coin.
+    pub fee_amount: u64,
+    /// Pool sqrt price after the swap, Q64.64.
+    pub sqrt_price_after: u128,
+    /// Spot price (B per A, raw units) before and after the swap.
+    pub price_before: f64,
+    pub price_after: f64,
+    /// Execution price shortfall against the spot price, in basis points.
+    pub price_impact_bps: f64,
+    /// Initialized ticks crossed, in crossing order.
+    pub ticks_crossed: Vec<i32>,
+}
+
+impl PoolState {
+    /// Read the pool object and its tick table.
+    pub async fn fetch(client: &mut Client, pool_id: Address) -> Result<PoolState, QuantError> {
+        let mut ledger_client = client.ledger_client();
+
+        let mut request = GetObjectRequest::new(&pool_id);
+        request.read_mask = Some(FieldMask {
+            paths: vec!["object_id".to_string(), "json".to_string()],
+        });
+
+        let response = ledger_client
+            .get_object(request)
+            .await
+            .map_err(|status| QuantError::object_rpc(pool_id, status))?
+            .into_inner();
+        let json = response
+            .object
+            .and_then(|object| object.json)
+            .ok_or_else(|| QuantError::invalid_response("Pool object missing json"))?;
+
+        let state = PoolState::load(client, &json).await?;
+        debug_quote(&format!(
+            "[PoolState::fetch] pool={pool_id}, sqrt_price={}, liquidity={}, tick={}, ticks={}",
+            state.sqrt_price,
+            state.liquidity,
+            state.tick_index,
+            state.ticks.len()
+        ));
+        Ok(state)
+    }
+
+    /// Pool state from an already fetched pool object JSON, reading only the
+    /// tick table.
+    pub async fn load(client: &mut Client, json: &Value) -> Result<PoolState, QuantError> {
+        let mut state = PoolState::from_json(json)?;
+        let ticks_table: Address = error::parse(
+            json_str(json_field(json, FIELD_TICKS)?, "id")
+                .ok_or_else(|| QuantError::invalid_response("Missing ticks table id in pool object"))?,
+            "ticks table id",
+        )?;
+        state.ticks = fetch_ticks(client, ticks_table).await?;
+        Ok(state)
+    }
+
+    /// Pool scalars from the object JSON; ticks are left empty.
+    pub fn from_json(json: &Value) -> Result<PoolState, QuantError> {
+        Ok(PoolState {
+            sqrt_price: json_u128(json_field(json, FIELD_SQRT_PRICE)?)
+                .ok_or_else(|| QuantError::invalid_response("Malformed sqrt_price in pool object"))?,
+            liquidity: json_u128(json_field(json, FIELD_LIQUIDITY)?)
+                .ok_or_else(|| QuantError::invalid_response("Malformed liquidity in pool object"))?,
+            tick_index: json_i32(json_field(json, FIELD_TICK_INDEX)?)
+                .ok_or_else(|| QuantError::invalid_response("Malformed tick_index in pool object"))?,
+            tick_spacing: json_u128(json_field(json, FIELD_TICK_SPACING)?)
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| QuantError::invalid_response("Malformed tick_spacing in pool object"))?,
+            fee_rate: json_u128(json_field(json, FIELD_FEE_RATE)?)
+                .and_then(|v| u64::try_from(v).ok())
+                .ok_or_else(|| QuantError::invalid_response("Malformed swap_fee_rate in pool object"))?,
+            ticks: BTreeMap::new(),
+        })
+    }
+
+    /// Spot price (B per A, raw units).
+    pub fn price(&self) -> f64 {
+        sqrt_price_to_price(self.sqrt_price as f64 / Q64)
+    }
+
+    /// Simulate selling `amount_in` of the input coin (A if `a_to_b`).
+    ///
+    /// Walks the initialized ticks like the on-chain concentrated-liquidity
+    /// swap loop, in f64 arithmetic; results are estimates, not exact to the
+    /// last unit.
+    pub fn quote(&self, amount_in: u64, a_to_b: bool) -> Quote {
+        let fee = self.fee_rate as f64 / FEE_RATE_DENOMINATOR;
+        let mut sqrt_price = self.sqrt_price as f64 / Q64;
+        let mut liquidity = self.liquidity as f64;
+        let mut remaining = amount_in as f64;
+        let mut amount_out = 0.0;
+        let mut fee_amount = 0.0;
+        let mut ticks_crossed = Vec::new();
+
+        // Initialized ticks in crossing order.
+        let next_ticks: Vec<(i32, i128)> = if a_to_b {
+            self.ticks
+                .range(..=self.tick_index)
+                .rev()
+                .map(|(tick, net)| (*tick, *net))
+                .collect()
+        } else {
+            self.ticks
+                .range(self.tick_index + 1..)
+                .map(|(tick, net)| (*tick, *net))
+                .collect()
+        };
+        let mut next_ticks = next_ticks.into_iter();
+
+        while remaining > 0.0 {
+            let next = next_ticks.next();
+            let target = match next {
+                Some((tick, _)) => tick_to_sqrt_price(tick),
+                // No initialized tick left: the rest swaps against the
+                // current liquidity without bound.
+                None if a_to_b => 0.0,
+                None => f64::INFINITY,
+            };
+
+            if liquidity > 0.0 {
+                let remaining_less_fee = remaining * (1.0 - fee);
+                let max_in = if a_to_b {
+                    liquidity * (1.0 / target - 1.0 / sqrt_price)
+                } else {
+                    liquidity * (target - sqrt_price)
+                };
+
+                if remaining_less_fee < max_in {
+                    // Swap ends inside this range.
+                    let new_sqrt_price = if a_to_b {
+                        1.0 / (1.0 / sqrt_price + remaining_less_fee / liquidity)
+                    } else {
+                        sqrt_price + remaining_less_fee / liquidity
+                    };
+                    amount_out += amount_between(sqrt_price, new_sqrt_price, liquidity, a_to_b);
+                    fee_amount += remaining - remaining_less_fee;
+                    sqrt_price = new_sqrt_price;
+                    remaining = 0.0;
+                    break;
+                }
+
+                // Range fully consumed up to the next tick.
+                amount_out += amount_between(sqrt_price, target, liquidity, a_to_b);
+                let step_fee = max_in * fee / (1.0 - fee);
+                fee_amount += step_fee;
+                remaining -= max_in + step_fee;
+            }
+
+            let Some((tick, liquidity_net)) = next else {
+                break;
+            };
+            sqrt_price = target;
+            ticks_crossed.push(tick);
+            liquidity = if a_to_b {
+                liquidity - liquidity_net as f64
+            } else {
+                liquidity + liquidity_net as f64
+            }
+            .max(0.0);
+        }
+
+        let consumed = amount_in as f64 - remaining.max(0.0);
+        let price_before = self.price();
+        let spot_out = if a_to_b {
+            consumed * price_before
+        } else {
+            consumed / price_before
+        };
+        let price_impact_bps = if spot_out > 0.0 {
+            (1.0 - amount_out / spot_out) * 10_000.0
+        } else {
+            0.0
+        };
+
+        Quote {
+            amount_in: consumed.round() as u64,
+            amount_out: amount_out.floor() as u64,
+            fee_amount: fee_amount.round() as u64,
+            sqrt_price_after: (sqrt_price * Q64) as u128,
+            price_before,
+            price_after: sqrt_price_to_price(sqrt_price),
+            price_impact_bps,
+            ticks_crossed,
+        }
+    }
+
+    /// Smallest input whose quote buys at least `amount_out`, `None` if the
+    /// pool cannot fill it.
+    ///
+    /// Outputs grow with the input, so the input is found by bisection over
+    /// `quote`.
+    pub fn quote_exact_out(&self, amount_out: u64, a_to_b: bool) -> Option<Quote> {
+        // Double the input until it buys enough: `low` never does, `high` does.
+        let mut low = 0u64;
+        let mut high = 1u64;
+        loop {
+            let quote = self.quote(high, a_to_b);
+            if quote.amount_out >= amount_out {
+                break;
+            }
+            if quote.amount_in < high || high == u64::MAX {
+                return None;
+            }
+            low = high;
+            high = high.saturating_mul(2);
+        }
+
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if self.quote(mid, a_to_b).amount_out >= amount_out {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        Some(self.quote(high, a_to_b))
+    }
+}
+
+/// Output amount when the price moves from `from` to `to` (real sqrt prices).
+fn amount_between(from: f64, to: f64, liquidity: f64, a_to_b: bool) -> f64 {
+    if a_to_b {
+        // Coin B out.
+        liquidity * (from - to)
+    } else {
+        // Coin A out.
+        liquidity * (1.0 / from - 1.0 / to)
+    }
+}
+
+/// Real sqrt price at a tick: sqrt(1.0001^tick).
+fn tick_to_sqrt_price(tick: i32) -> f64 {
+    (tick as f64 * 1.0001f64.ln() / 2.0).exp()
+}
+
+fn sqrt_price_to_price(sqrt_price: f64) -> f64 {
+    sqrt_price * sqrt_price
+}
+
+/// List every initialized tick of the pool's tick table.
+///
+/// Each dynamic field is `I32 -> TickInfo`; the name is the tick's `u32` bits
+/// and `TickInfo` starts with `liquidity_gross: u128, liquidity_net: I128`.
+async fn fetch_ticks(
+    client: &mut Client,
+    ticks_table: Address,
+) -> Result<BTreeMap<i32, i128>, QuantError> {
+    let mut state_client = client.state_client();
+    let mut ticks = BTreeMap::new();
+    let mut page_token = None;
+
+    loop {
+        let mut request = ListDynamicFieldsRequest::default();
+        request.parent = Some(ticks_table.to_string());
+        request.page_size = Some(TICKS_PAGE_SIZE);
+        request.page_token = page_token;
+        request.read_mask = Some(FieldMask {
+            paths: vec!["name".to_string(), "value".to_string()],
+        });
+
+        let response = state_client.list_dynamic_fields(request).await?.into_inner();
+        for field in response.dynamic_fields {
+            let name = field.name.as_ref().and_then(|bcs| bcs.value.as_deref());
+            let value = field.value.as_ref().and_then(|bcs| bcs.value.as_deref());
+            let (Some(name), Some(value)) = (name, value) else {
+                return Err(QuantError::invalid_response("Tick table entry without name or value"));
+            };
+
+            let tick_bits: [u8; 4] = name
+                .get(..4)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| QuantError::invalid_response("Malformed tick index in tick table"))?;
+            let net_bits: [u8; 16] = value
+                .get(16..32)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| QuantError::invalid_response("Malformed tick info in tick table"))?;
+
+            ticks.insert(
+                u32::from_le_bytes(tick_bits) as i32,
+                u128::from_le_bytes(net_bits) as i128,
+            );
+        }
+
+        match response.next_page_token {
+            Some(token) if !token.is_empty() => page_token = Some(token),
+            _ => break,
+        }
+    }
+
+    Ok(ticks)
+}
+
+/// Field of a JSON struct value.
+pub fn json_field<'a>(json: &'a Value, field: &str) -> Result<&'a Value, QuantError> {
+    match &json.kind {
+        Some(value::Kind::StructValue(s)) => s
+            .fields
+            .get(field)
+            .ok_or_else(|| QuantError::InvalidResponse(format!("Missing field '{field}' in object json"))),
+        _ => Err(QuantError::InvalidResponse(format!(
+            "Expected a json struct holding '{field}'"
+        ))),
+    }
+}
+
+/// Unsigned integer rendered as a JSON string (u64 and wider) or number.
+pub fn json_u128(json: &Value) -> Option<u128> {
+    match &json.kind {
+        Some(value::Kind::StringValue(s)) => s.parse().ok(),
+        Some(value::Kind::NumberValue(n)) if *n >= 0.0 => Some(*n as u128),
+        _ => None,
+    }
+}
+
+/// Momentum `I32 { bits: u32 }` rendered as JSON.
+fn json_i32(json: &Value) -> Option<i32> {
+    let bits = match &json.kind {
+        Some(value::Kind::StructValue(s)) => json_u128(s.fields.get("bits")?)?,
+        _ => json_u128(json)?,
+    };
+    u32::try_from(bits).ok().map(|bits| bits as i32)
+}
+
+fn json_str<'a>(json: &'a Value, field: &str) -> Option<&'a str> {
+    match &json_field(json, field).ok()?.kind {
+        Some(value::Kind::StringValue(s)) => Some(s),
+        _ => None,
+    }
+}
+
+fn debug_quote(msg: &str) {
+    if DEBUG_QUOTE {
+        eprintln!("{msg}");
+    }
+}
+
+#[cfg(test)]
+impl PoolState {
+    /// Test pool at price 1 (tick 0) with the given initialized ticks.
+    pub(crate) fn at_price_one(liquidity: u128, fee_rate: u64, ticks: &[(i32, i128)]) -> PoolState {
+        PoolState {
+            sqrt_price: 1 << 64,
+            liquidity,
+            tick_index: 0,
+            tick_spacing: 1,
+            fee_rate,
+            ticks: ticks.iter().copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIQUIDITY: u128 = 1_000_000_000_000;
+
+    #[test]
+    fn quote_charges_the_fee_on_the_input() {
+        let quote = PoolState::at_price_one(LIQUIDITY, 3_000, &[]).quote(1_000_000, true);
+        assert_eq!(quote.amount_in, 1_000_000);
+        assert_eq!(quote.fee_amount, 3_000);
+        // 997_000 after the fee, minus a unit of price impact.
+        assert_eq!(quote.amount_out, 996_999);
+        assert!(quote.ticks_crossed.is_empty());
+    }
+
+    #[test]
+    fn quote_moves_the_price_against_the_trade() {
+        let pool = PoolState::at_price_one(LIQUIDITY, 0, &[]);
+        let a_to_b = pool.quote(1_000_000_000, true);
+        assert!(a_to_b.sqrt_price_after < pool.sqrt_price);
+        assert!(a_to_b.amount_out < 1_000_000_000);
+        assert!(a_to_b.price_impact_bps > 0.0);
+
+        let b_to_a = pool.quote(1_000_000_000, false);
+        assert!(b_to_a.sqrt_price_after > pool.sqrt_price);
+        assert!(b_to_a.amount_out < 1_000_000_000);
+    }
+
+    #[test]
+    fn quote_crosses_ticks_in_swap_direction() {
+        // Half the liquidity ends at tick -10.
+        let ticks = [(-10, LIQUIDITY as i128 / 2), (10, -(LIQUIDITY as i128))];
+        let crossing = PoolState::at_price_one(LIQUIDITY, 0, &ticks);
+        let quote = crossing.quote(1_000_000_000, true);
+        assert_eq!(quote.ticks_crossed, vec![-10]);
+        assert_eq!(quote.amount_in, 1_000_000_000);
+
+        // The thinner range below the tick costs more price impact.
+        let flat = PoolState::at_price_one(LIQUIDITY, 0, &[]).quote(1_000_000_000, true);
+        assert!(quote.amount_out < flat.amount_out);
+        assert!(quote.sqrt_price_after < flat.sqrt_price_after);
+    }
+
+    #[test]
+    fn quote_stops_when_liquidity_is_exhausted() {
+        // All liquidity ends at tick -10, about 500_100_000 of A in.
+        let pool = PoolState::at_price_one(LIQUIDITY, 0, &[(-10, LIQUIDITY as i128)]);
+        let quote = pool.quote(1_000_000_000, true);
+        assert_eq!(quote.ticks_crossed, vec![-10]);
+        assert!(quote.amount_in < 1_000_000_000);
+        assert!((500_000_000..500_200_000).contains(&quote.amount_in));
+        assert_eq!(quote.sqrt_price_after, (tick_to_sqrt_price(-10) * Q64) as u128);
+    }
+
+    #[test]
+    fn quote_exact_out_finds_the_smallest_input() {
+        let pool = PoolState::at_price_one(LIQUIDITY, 3_000, &[]);
+        let quote = pool.quote_exact_out(500_000, true).unwrap();
+        assert!(quote.amount_out >= 500_000);
+        assert!(pool.quote(quote.amount_in - 1, true).amount_out < 500_000);
+    }
+
+    #[test]
+    fn quote_exact_out_rejects_outputs_beyond_liquidity() {
+        let pool = PoolState::at_price_one(LIQUIDITY, 0, &[(-10, LIQUIDITY as i128)]);
+        assert!(pool.quote_exact_out(600_000_000, true).is_none());
+        assert!(pool.quote_exact_out(400_000_000, true).is_some());
+    }
+}
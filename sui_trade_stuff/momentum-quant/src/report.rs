@@ -0,0 +1,189 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::Serialize;
+use sui_rpc::proto::sui::rpc::v2::ExecutedTransaction;
+use sui_sdk_types::{Address, TypeTag};
+
+use crate::error::QuantError;
+use crate::finality::{self, Finality};
+use crate::momentum;
+use crate::outcome::{AbortContext, ExecutionOutcome};
+use crate::simulate::{BalanceDelta, GasUsed};
+
+/// Enable / disable debug logs inside report module.
+const DEBUG_REPORT: bool = false;
+
+/// Set while stdout is reserved for a JSON report.
+static STATUS_ON_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Accounting record of one executed swap.
+///
+/// Amounts are in the smallest unit of each coin. The sender's SUI delta
+/// includes the gas payment; `amount_in` / `amount_out` exclude it.
+#[derive(Clone, Debug, Serialize)]
+pub struct TradeReport {
+    pub digest: String,
+    /// Checkpoint of the transaction, if already known at execution or
+    /// waited for.
+    pub checkpoint: Option<u64>,
+    /// Timestamp of that checkpoint, in ms since the Unix epoch.
+    pub checkpoint_timestamp_ms: Option<u64>,
+    /// Why waiting for the checkpoint failed; the swap itself executed.
+    pub finality_error: Option<String>,
+    pub success: bool,
+    pub sender: String,
+    pub input_type: String,
+    pub output_type: String,
+    pub amount_in: i128,
+    pub amount_out: i128,
+    /// `amount_out / amount_in`, absent when nothing was spent.
+    pub execution_price: Option<f64>,
+    /// Net balance change of the sender, one entry per coin type.
+    pub balance_deltas: Vec<CoinDelta>,
+    pub gas: GasUsed,
+    pub net_gas_cost: i128,
+    /// Wall time from the start of the command to the execution response.
+    pub elapsed_ms: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CoinDelta {
+    pub coin_type: String,
+    pub amount: i128,
+}
+
+impl TradeReport {
+    pub fn from_executed(
+        transaction: &ExecutedTransaction,
+        sender: &Address,
+        input_type: &TypeTag,
+        output_type: &TypeTag,
+        elapsed: Duration,
+    ) -> Result<TradeReport, QuantError> {
+        // Only the status, digest and gas are reported, no failure reason.
+        let outcome = ExecutionOutcome::from_executed(transaction, &AbortContext::default())?;
+        let digest = outcome
+            .digest
+            .clone()
+            .ok_or_else(|| QuantError::invalid_response("Executed transaction without digest"))?;
+
+        // Sum the sender's changes per coin type, keeping first-seen order.
+        let mut balance_deltas: Vec<CoinDelta> = Vec::new();
+        for change in &transaction.balance_changes {
+            let delta = BalanceDelta::from_proto(change)?;
+            if delta.address.parse::<Address>().ok().as_ref() != Some(sender) {
+                continue;
+            }
+            match balance_deltas.iter_mut().find(|entry| entry.coin_type == delta.coin_type) {
+                Some(entry) => entry.amount += delta.amount,
+                None => balance_deltas.push(CoinDelta {
+                    coin_type: delta.coin_type,
+                    amount: delta.amount,
+                }),
+            }
+        }
+
+        // The sender pays gas in SUI: add it back to get the traded amount.
+        let net_gas_cost = outcome.gas_used.net_cost();
+        let traded = |coin_type: &TypeTag| -> i128 {
+            let delta: i128 = balance_deltas
+                .iter()
+                .filter(|entry| entry.coin_type.parse::<TypeTag>().ok().as_ref() == Some(coin_type))
+                .map(|entry| entry.amount)
+                .sum();
+            if momentum::is_sui(coin_type) { delta + net_gas_cost } else { delta }
+        };
+        let amount_in = -traded(input_type);
+        let amount_out = traded(output_type);
+
+        let report = TradeReport {
+            digest,
+            checkpoint: transaction.checkpoint,
+            checkpoint_timestamp_ms: transaction.timestamp.as_ref().and_then(finality::timestamp_ms),
+            finality_error: None,
+            success: outcome.success,
+            sender: sender.to_string(),
+            input_type: input_type.to_string(),
+            output_type: output_type.to_string(),
+            amount_in,
+            amount_out,
+            execution_price: (amount_in > 0).then(|| amount_out as f64 / amount_in as f64),
+            balance_deltas,
+            gas: outcome.gas_used,
+            net_gas_cost,
+            elapsed_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+        };
+        debug_report(&format!("[TradeReport::from_executed] {report:?}"));
+        Ok(report)
+    }
+
+    /// Record the checkpoint the transaction was finalized in.
+    pub fn set_finality(&mut self, finality: Finality) {
+        self.checkpoint = Some(finality.checkpoint);
+        self.checkpoint_timestamp_ms = finality.timestamp_ms;
+    }
+
+    pub fn to_json(&self) -> Result<String, QuantError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Append the report as one JSON line to `path`.
+    pub fn append_to(&self, path: &Path) -> Result<(), QuantError> {
+        let line = self.to_json()?;
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{line}")?;
+        Ok(())
+    }
+
+    pub fn print_table(&self) {
+        println!("Trade report:");
+        println!("  {:<16} {}", "Digest", self.digest);
+        println!(
+            "  {:<16} {}",
+            "Checkpoint",
+            self.checkpoint.map_or("pending".to_string(), |checkpoint| checkpoint.to_string())
+        );
+        if let Some(timestamp_ms) = self.checkpoint_timestamp_ms {
+            println!("  {:<16} {timestamp_ms} ms", "Checkpoint time");
+        }
+        if let Some(error) = &self.finality_error {
+            println!("  {:<16} {error}", "Finality error");
+        }
+        println!("  {:<16} {}", "Status", if self.success { "success" } else { "failure" });
+        println!("  {:<16} {} {}", "Amount in", self.amount_in, self.input_type);
+        println!("  {:<16} {} {}", "Amount out", self.amount_out, self.output_type);
+        match self.execution_price {
+            Some(price) => println!("  {:<16} {price}", "Execution price"),
+            None => println!("  {:<16} -", "Execution price"),
+        }
+        println!("  {:<16} {}", "Computation", self.gas.computation_cost);
+        println!("  {:<16} {}", "Storage", self.gas.storage_cost);
+        println!("  {:<16} {}", "Storage rebate", self.gas.storage_rebate);
+        println!("  {:<16} {}", "Net gas", self.net_gas_cost);
+        println!("  {:<16} {} ms", "Elapsed", self.elapsed_ms);
+        println!("  Balance changes:");
+        for delta in &self.balance_deltas {
+            println!("    {:>24} {}", delta.amount, delta.coin_type);
+        }
+    }
+}
+
+/// Reserve stdout for a JSON report: `status!` lines go to stderr from now
+/// on, so the report can be piped as is.
+pub fn reserve_stdout() {
+    STATUS_ON_STDERR.store(true, Ordering::Relaxed);
+}
+
+pub fn status_on_stderr() -> bool {
+    STATUS_ON_STDERR.load(Ordering::Relaxed)
+}
+
+fn debug_report(msg: &str) {
+    if DEBUG_REPORT {
+        eprintln!("{msg}");
+    }
+}
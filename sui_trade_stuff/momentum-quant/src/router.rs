@@ -0,0 +1,423 @@
+use sui_rpc::Client;
+use sui_rpc::proto::sui::rpc::v2::Object;
+use sui_sdk_types::{Address, TypeTag};
+
+use crate::config::{NetworkConfig, RouteGasConfig};
+use crate::error::{self, QuantError};
+use crate::momentum::{RouteHop, SplitLeg, SwapPool};
+use crate::objects;
+use crate::quote::PoolState;
+
+/// Enable / disable debug logs inside router module.
+const DEBUG_ROUTER: bool = false;
+
+/// Number of chunks the split optimizer hands out one at a time.
+const SPLIT_STEPS: u64 = 100;
+
+/// Coin type gas is paid in.
+const SUI_TYPE: &str = "0x2::sui::SUI";
+
+/// A configured pool with its current on-chain state.
+#[derive(Clone, Debug)]
+pub struct GraphPool {
+    /// Registry name, e.g. `SUI-USDC`.
+    pub name: String,
+    pub pool_id: Address,
+    pub swap_pool: SwapPool,
+    pub state: PoolState,
+    /// Pool object read with `objects::POOL_FIELDS`, for the swap input.
+    pub object: Object,
+}
+
+/// Every configured pool of a network; coin types are the nodes and pools
+/// the (bidirectional) edges.
+pub struct PoolGraph {
+    pools: Vec<GraphPool>,
+    /// Configured gas cost routes are ranked with.
+    route_gas: RouteGasConfig,
+}
+
+/// A quoted path through the pool graph.
+#[derive(Clone, Debug)]
+pub struct RouteCandidate {
+    /// Registry names of the pools, in trading order.
+    pub pools: Vec<String>,
+    pub pool_ids: Vec<Address>,
+    pub hops: Vec<RouteHop>,
+    /// Locally simulated output of the whole path.
+    pub amount_out: u64,
+    /// Configured gas cost in MIST.
+    pub gas_cost: u64,
+    /// Gas cost in units of the output coin, `None` if SUI cannot be priced
+    /// in the output coin through the graph.
+    pub gas_cost_in_output: Option<u64>,
+}
+
+impl RouteCandidate {
+    /// Output minus the gas cost, both in output coin units; `None` if the
+    /// gas cost cannot be priced in the output coin.
+    pub fn net_amount_out(&self) -> Option<i128> {
+        self.gas_cost_in_output
+            .map(|gas_cost| self.amount_out as i128 - gas_cost as i128)
+    }
+}
+
+/// An order split across parallel pools of one pair.
+#[derive(Clone, Debug)]
+pub struct SplitPlan {
+    /// Registry names and ids of the pools with a non-zero leg, in leg order.
+    pub pools: Vec<String>,
+    pub pool_ids: Vec<Address>,
+    pub legs: Vec<SplitLeg>,
+    /// Locally simulated output of each leg.
+    pub leg_amounts_out: Vec<u64>,
+    pub amount_out: u64,
+}
+
+impl PoolGraph {
+    /// Fetch every pool in the network registry with one batched read, then
+    /// the tick table of each.
+    pub async fn load(client: &mut Client, network: &NetworkConfig) -> Result<PoolGraph, QuantError> {
+        let mut requests = Vec::with_capacity(network.pools.len());
+        for pool_config in network.pools.values() {
+            requests.push((error::parse(&pool_config.id, "pool id")?, objects::POOL_FIELDS));
+        }
+        let objects = objects::fetch_objects(client, &requests).await?;
+
+        let mut pools = Vec::with_capacity(network.pools.len());
+        for (((name, pool_config), (pool_id, _)), object) in network.pools.iter().zip(&requests).zip(objects) {
+            let swap_pool = SwapPool::from_config(&network.packages, pool_config)?;
+            let json = object
+                .json
+                .as_ref()
+                .ok_or_else(|| QuantError::invalid_response("Missing json in pool object"))?;
+            let state = PoolState::load(client, json).await?;
+            debug_router(&format!(
+                "[PoolGraph::load] {name}: {} / {}, liquidity={}",
+                swap_pool.coin_type_a, swap_pool.coin_type_b, state.liquidity
+            ));
+            pools.push(GraphPool {
+                name: name.clone(),
+                pool_id: *pool_id,
+                swap_pool,
+                state,
+                object,
+            });
+        }
+        Ok(PoolGraph {
+            pools,
+            route_gas: network.route_gas.clone(),
+        })
+    }
+
+    pub fn pools(&self) -> &[GraphPool] {
+        &self.pools
+    }
+
+    pub fn pool(&self, pool_id: &Address) -> Option<&GraphPool> {
+        self.pools.iter().find(|pool| pool.pool_id == *pool_id)
+    }
+
+    /// Quote every path of at most `max_hops` pools from `input_type` to
+    /// `output_type`, best net output first.
+    ///
+    /// Paths that the pools cannot fill completely are dropped. If no path
+    /// prices SUI in the output coin, no route has a net output and they
+    /// are ranked by output, then by fewer hops.
+    pub fn find_routes(
+        &self,
+        input_type: &TypeTag,
+        output_type: &TypeTag,
+        amount_in: u64,
+        max_hops: usize,
+    ) -> Result<Vec<RouteCandidate>, QuantError> {
+        let sui_type: TypeTag = error::parse(SUI_TYPE, "coin type")?;
+        // Gas is priced along the best spot path from SUI to the output coin.
+        let sui_rate = if *output_type == sui_type {
+            Some(1.0)
+        } else {
+            self.paths(&sui_type, output_type, max_hops)
+                .iter()
+                .map(|path| self.spot_rate(path))
+                .max_by(f64::total_cmp)
+        };
+
+        let mut candidates = Vec::new();
+        for path in self.paths(input_type, output_type, max_hops) {
+            let Some(amount_out) = self.quote_path(&path, amount_in) else {
+                debug_router(&format!("[find_routes] path {path:?} cannot be filled"));
+                continue;
+            };
+
+            let gas_cost = self.route_gas.cost(path.len());
+            let gas_cost_in_output = sui_rate.map(|rate| (gas_cost as f64 * rate).ceil() as u64);
+
+            candidates.push(RouteCandidate {
+                pools: path.iter().map(|(i, _)| self.pools[*i].name.clone()).collect(),
+                pool_ids: path.iter().map(|(i, _)| self.pools[*i].pool_id).collect(),
+                hops: path
+                    .iter()
+                    .map(|(i, a_to_b)| RouteHop {
+                        swap_pool: self.pools[*i].swap_pool.clone(),
+                        a_to_b: *a_to_b,
+                    })
+                    .collect(),
+                amount_out,
+                gas_cost,
+                gas_cost_in_output,
+            });
+        }
+
+        candidates.sort_by_key(|candidate| {
+            let net_amount_out = candidate
+                .net_amount_out()
+                .unwrap_or(candidate.amount_out as i128);
+            (std::cmp::Reverse(net_amount_out), candidate.hops.len())
+        });
+        Ok(candidates)
+    }
+
+    /// Best route by net output.
+    pub fn best_route(
+        &self,
+        input_type: &TypeTag,
+        output_type: &TypeTag,
+        amount_in: u64,
+        max_hops: usize,
+    ) -> Result<RouteCandidate, QuantError> {
+        self.find_routes(input_type, output_type, amount_in, max_hops)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                QuantError::InvalidInput(format!(
+                    "No route of at most {max_hops} hops from {input_type} to {output_type}"
+                ))
+            })
+    }
+
+    /// Split `amount_in` across every pool trading `input_type` directly for
+    /// `output_type`.
+    pub fn split_order(
+        &self,
+        input_type: &TypeTag,
+        output_type: &TypeTag,
+        amount_in: u64,
+    ) -> Result<SplitPlan, QuantError> {
+        let direct: Vec<(usize, bool)> = self
+            .pools
+            .iter()
+            .enumerate()
+            .filter_map(|(i, pool)| {
+                let (a, b) = (&pool.swap_pool.coin_type_a, &pool.swap_pool.coin_type_b);
+                if a == input_type && b == output_type {
+                    Some((i, true))
+                } else if b == input_type && a == output_type {
+                    Some((i, false))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if direct.is_empty() {
+            return Err(QuantError::InvalidInput(format!(
+                "No configured pool trades {input_type} for {output_type}"
+            )));
+        }
+
+        let states: Vec<(&PoolState, bool)> = direct
+            .iter()
+            .map(|(i, a_to_b)| (&self.pools[*i].state, *a_to_b))
+            .collect();
+        let allocation = optimize_split(&states, amount_in);
+
+        let mut plan = SplitPlan {
+            pools: Vec::new(),
+            pool_ids: Vec::new(),
+            legs: Vec::new(),
+            leg_amounts_out: Vec::new(),
+            amount_out: 0,
+        };
+        for ((i, a_to_b), leg_amount) in direct.into_iter().zip(allocation) {
+            if leg_amount == 0 {
+                continue;
+            }
+            let pool = &self.pools[i];
+            let leg_out = pool.state.quote(leg_amount, a_to_b).amount_out;
+            plan.pools.push(pool.name.clone());
+            plan.pool_ids.push(pool.pool_id);
+            plan.legs.push(SplitLeg {
+                hop: RouteHop {
+                    swap_pool: pool.swap_pool.clone(),
+                    a_to_b,
+                },
+                amount_in: leg_amount,
+            });
+            plan.leg_amounts_out.push(leg_out);
+            plan.amount_out += leg_out;
+        }
+        Ok(plan)
+    }
+
+    /// Simple paths as `(pool index, a_to_b)`, using each pool and coin type
+    /// at most once.
+    fn paths(&self, from: &TypeTag, to: &TypeTag, max_hops: usize) -> Vec<Vec<(usize, bool)>> {
+        let mut paths = Vec::new();
+        let mut path = Vec::new();
+        let mut visited = vec![from.clone()];
+        self.walk(from, to, max_hops, &mut path, &mut visited, &mut paths);
+        paths
+    }
+
+    fn walk(
+        &self,
+        current: &TypeTag,
+        to: &TypeTag,
+        max_hops: usize,
+        path: &mut Vec<(usize, bool)>,
+        visited: &mut Vec<TypeTag>,
+        paths: &mut Vec<Vec<(usize, bool)>>,
+    ) {
+        if path.len() == max_hops {
+            return;
+        }
+
+        for (i, pool) in self.pools.iter().enumerate() {
+            let (a_to_b, next) = if pool.swap_pool.coin_type_a == *current {
+                (true, &pool.swap_pool.coin_type_b)
+            } else if pool.swap_pool.coin_type_b == *current {
+                (false, &pool.swap_pool.coin_type_a)
+            } else {
+                continue;
+            };
+            if visited.contains(next) {
+                continue;
+            }
+
+            path.push((i, a_to_b));
+            if next == to {
+                paths.push(path.clone());
+            } else {
+                visited.push(next.clone());
+                self.walk(next, to, max_hops, path, visited, paths);
+                visited.pop();
+            }
+            path.pop();
+        }
+    }
+
+    /// Chain local quotes along `path`; `None` if a hop cannot absorb its input.
+    fn quote_path(&self, path: &[(usize, bool)], amount_in: u64) -> Option<u64> {
+        let mut amount = amount_in;
+        for (i, a_to_b) in path {
+            let quote = self.pools[*i].state.quote(amount, *a_to_b);
+            if quote.amount_in < amount || quote.amount_out == 0 {
+                return None;
+            }
+            amount = quote.amount_out;
+        }
+        Some(amount)
+    }
+
+    /// Output per unit of input along `path` at spot prices, fees excluded.
+    fn spot_rate(&self, path: &[(usize, bool)]) -> f64 {
+        path.iter().fold(1.0, |rate, (i, a_to_b)| {
+            let price = self.pools[*i].state.price();
+            if *a_to_b { rate * price } else { rate / price }
+        })
+    }
+}
+
+/// Allocate `amount_in` across `pools` (state, a_to_b) to maximize the
+/// total output.
+///
+/// Hands out `SPLIT_STEPS` chunks greedily, each to the pool with the best
+/// marginal output for it; with concave outputs this ends with the marginal
+/// prices of all used pools (nearly) equal.
+pub fn optimize_split(pools: &[(&PoolState, bool)], amount_in: u64) -> Vec<u64> {
+    let mut allocation = vec![0u64; pools.len()];
+    let mut outputs = vec![0u64; pools.len()];
+    if pools.is_empty() || amount_in == 0 {
+        return allocation;
+    }
+
+    let chunk = (amount_in / SPLIT_STEPS).max(1);
+    let mut remaining = amount_in;
+    while remaining > 0 {
+        let step = chunk.min(remaining);
+        let best = pools
+            .iter()
+            .enumerate()
+            .map(|(i, (state, a_to_b))| {
+                let out = state.quote(allocation[i] + step, *a_to_b).amount_out;
+                (i, out, out.saturating_sub(outputs[i]))
+            })
+            .max_by_key(|(_, _, marginal)| *marginal);
+        let Some((i, out, _)) = best else {
+            break;
+        };
+
+        allocation[i] += step;
+        outputs[i] = out;
+        remaining -= step;
+    }
+
+    debug_router(&format!("[optimize_split] allocation={allocation:?}, outputs={outputs:?}"));
+    allocation
+}
+
+fn debug_router(msg: &str) {
+    if DEBUG_ROUTER {
+        eprintln!("{msg}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pool at price 1 with a 0.3% fee, `liquidity` and no initialized ticks.
+    fn pool(liquidity: u128) -> PoolState {
+        PoolState::at_price_one(liquidity, 3_000, &[])
+    }
+
+    fn total_out(pools: &[(&PoolState, bool)], allocation: &[u64]) -> u64 {
+        pools
+            .iter()
+            .zip(allocation)
+            .map(|((state, a_to_b), amount)| state.quote(*amount, *a_to_b).amount_out)
+            .sum()
+    }
+
+    #[test]
+    fn optimize_split_allocates_nothing_without_input() {
+        let deep = pool(1_000_000_000);
+        assert_eq!(optimize_split(&[(&deep, true), (&deep, true)], 0), vec![0, 0]);
+        assert!(optimize_split(&[], 1_000).is_empty());
+    }
+
+    #[test]
+    fn optimize_split_allocates_the_whole_input() {
+        let (deep, shallow) = (pool(1_000_000_000), pool(100_000_000));
+        let allocation = optimize_split(&[(&deep, true), (&shallow, false)], 10_000_003);
+        assert_eq!(allocation.iter().sum::<u64>(), 10_000_003);
+    }
+
+    #[test]
+    fn optimize_split_balances_equal_pools() {
+        let deep = pool(1_000_000_000);
+        let allocation = optimize_split(&[(&deep, true), (&deep, true)], 10_000_000);
+        assert!(allocation[0].abs_diff(allocation[1]) <= 10_000_000 / SPLIT_STEPS);
+    }
+
+    #[test]
+    fn optimize_split_favors_deeper_pools() {
+        let (deep, shallow) = (pool(1_000_000_000), pool(100_000_000));
+        let pools = [(&deep, true), (&shallow, true)];
+        let allocation = optimize_split(&pools, 50_000_000);
+        assert!(allocation[0] > allocation[1]);
+        assert!(allocation[1] > 0);
+
+        // The split beats sending everything to the deeper pool.
+        assert!(total_out(&pools, &allocation) > deep.quote(50_000_000, true).amount_out);
+    }
+}
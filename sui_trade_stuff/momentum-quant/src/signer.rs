@@ -0,0 +1,96 @@
+use sui_crypto::ed25519::Ed25519PrivateKey;
+use sui_crypto::secp256k1::Secp256k1PrivateKey;
+use sui_crypto::secp256r1::Secp256r1PrivateKey;
+use sui_crypto::{SignatureError, SuiSigner};
+use sui_sdk_types::{Address, Transaction, UserSignature};
+
+use crate::error::QuantError;
+
+/// Signature scheme flags, as used in keystore entries and `suiprivkey` strings.
+const ED25519_FLAG: u8 = 0x00;
+const SECP256K1_FLAG: u8 = 0x01;
+const SECP256R1_FLAG: u8 = 0x02;
+
+/// Length of a private key, excluding the scheme flag.
+const PRIVATE_KEY_LENGTH: usize = 32;
+
+/// A private key of any signature scheme supported by Sui single-signer accounts.
+pub enum SuiKeyPair {
+    Ed25519(Ed25519PrivateKey),
+    Secp256k1(Secp256k1PrivateKey),
+    Secp256r1(Secp256r1PrivateKey),
+}
+
+impl SuiKeyPair {
+    /// Decode a `flag || private key` byte string.
+    pub fn from_flagged_bytes(bytes: &[u8]) -> Result<SuiKeyPair, QuantError> {
+        let (flag, key) = bytes
+            .split_first()
+            .ok_or_else(|| QuantError::Key("Invalid Sui private key format: empty key".to_string()))?;
+
+        let key: [u8; PRIVATE_KEY_LENGTH] = key.try_into().map_err(|_| {
+            QuantError::Key(format!(
+                "Invalid Sui private key length: {} bytes, expected {PRIVATE_KEY_LENGTH}",
+                key.len()
+            ))
+        })?;
+
+        match *flag {
+            ED25519_FLAG => Ok(SuiKeyPair::Ed25519(Ed25519PrivateKey::new(key))),
+            SECP256K1_FLAG => Ok(SuiKeyPair::Secp256k1(
+                Secp256k1PrivateKey::new(key)
+                    .map_err(|e| QuantError::Key(format!("Invalid secp256k1 private key: {e}")))?,
+            )),
+            SECP256R1_FLAG => Ok(SuiKeyPair::Secp256r1(Secp256r1PrivateKey::new(key))),
+            flag => Err(QuantError::Key(format!("Unsupported signature scheme flag {flag:#04x}"))),
+        }
+    }
+
+    /// Human readable name of the signature scheme.
+    pub fn scheme(&self) -> &'static str {
+        match self {
+            SuiKeyPair::Ed25519(_) => "ed25519",
+            SuiKeyPair::Secp256k1(_) => "secp256k1",
+            SuiKeyPair::Secp256r1(_) => "secp256r1",
+        }
+    }
+
+    /// `flag || public key` bytes, as stored in `sui.aliases`.
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match self {
+            SuiKeyPair::Ed25519(key) => {
+                bytes.push(ED25519_FLAG);
+                bytes.extend_from_slice(key.public_key().as_bytes());
+            }
+            SuiKeyPair::Secp256k1(key) => {
+                bytes.push(SECP256K1_FLAG);
+                bytes.extend_from_slice(key.public_key().as_bytes());
+            }
+            SuiKeyPair::Secp256r1(key) => {
+                bytes.push(SECP256R1_FLAG);
+                bytes.extend_from_slice(key.public_key().as_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Sui address controlled by this key.
+    pub fn derive_address(&self) -> Address {
+        match self {
+            SuiKeyPair::Ed25519(key) => key.public_key().derive_address(),
+            SuiKeyPair::Secp256k1(key) => key.public_key().derive_address(),
+            SuiKeyPair::Secp256r1(key) => key.public_key().derive_address(),
+        }
+    }
+
+    /// Sign a transaction with the key's own scheme.
+    pub fn sign_transaction(&self, tx: &Transaction) -> Result<UserSignature, QuantError> {
+        let signature: Result<UserSignature, SignatureError> = match self {
+            SuiKeyPair::Ed25519(key) => key.sign_transaction(tx),
+            SuiKeyPair::Secp256k1(key) => key.sign_transaction(tx),
+            SuiKeyPair::Secp256r1(key) => key.sign_transaction(tx),
+        };
+        signature.map_err(|e| QuantError::Signing(e.to_string()))
+    }
+}
@@ -0,0 +1,259 @@
+use prost_types::FieldMask;
+use serde::Serialize;
+use sui_rpc::Client;
+use sui_rpc::proto::sui::rpc::v2::{
+    BalanceChange, ExecutedTransaction, GasCostSummary, SimulateTransactionRequest,
+};
+use sui_sdk_types::{Address, Transaction, TypeTag};
+
+use crate::error::{self, QuantError};
+use crate::outcome::{AbortContext, ExecutionOutcome};
+
+/// Enable / disable debug logs inside simulate module.
+const DEBUG_SIMULATE: bool = false;
+
+/// Outcome of simulating a transaction against the current chain state.
+#[derive(Clone, Debug)]
+pub struct SimulationReport {
+    pub outcome: ExecutionOutcome,
+    pub balance_changes: Vec<BalanceDelta>,
+    /// Raw executed transaction as returned by the node.
+    pub transaction: ExecutedTransaction,
+}
+
+/// Gas charged by a transaction, in MIST.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct GasUsed {
+    pub computation_cost: u64,
+    pub storage_cost: u64,
+    pub storage_rebate: u64,
+    pub non_refundable_storage_fee: u64,
+}
+
+/// Change of one address's balance of one coin type.
+#[derive(Clone, Debug)]
+pub struct BalanceDelta {
+    pub address: String,
+    pub coin_type: String,
+    pub amount: i128,
+}
+
+/// Simulate `tx` without signatures and collect its effects, decoding
+/// failures with `context`.
+pub async fn simulate_transaction(
+    client: &mut Client,
+    tx: &Transaction,
+    context: &AbortContext,
+) -> Result<SimulationReport, QuantError> {
+    let mut exec_client = client.execution_client();
+
+    let mut request = SimulateTransactionRequest::default();
+    request.transaction = Some(tx.clone().into());
+    request.read_mask = Some(FieldMask {
+        paths: vec![
+            "transaction.effects".to_string(),
+            "transaction.balance_changes".to_string(),
+        ],
+    });
+
+    debug_simulate("[simulate_transaction] before simulate_transaction");
+    let response = exec_client
+        .simulate_transaction(request)
+        .await?
+        .into_inner();
+    debug_simulate("[simulate_transaction] after simulate_transaction");
+
+    let transaction = response
+        .transaction
+        .ok_or_else(|| QuantError::invalid_response("Simulation returned no transaction"))?;
+    SimulationReport::from_executed(transaction, context)
+}
+
+/// Gas budget for a transaction that used `gas_used` in simulation:
+/// (computation + storage) × `safety_margin`, capped at `max_budget`.
+///
+/// The rebate is ignored since the budget must cover the gross charge.
+pub fn estimate_gas_budget(
+    gas_used: &GasUsed,
+    safety_margin: f64,
+    max_budget: u64,
+) -> Result<u64, QuantError> {
+    if safety_margin.is_nan() || safety_margin < 1.0 {
+        return Err(QuantError::InvalidInput(format!(
+            "Gas safety margin {safety_margin} must be at least 1"
+        )));
+    }
+
+    let cost = gas_used
+        .computation_cost
+        .saturating_add(gas_used.storage_cost);
+    if cost > max_budget {
+        return Err(QuantError::InvalidInput(format!(
+            "Simulated gas cost {cost} exceeds the maximum budget {max_budget}"
+        )));
+    }
+
+    let budget = (cost as f64 * safety_margin).ceil().min(max_budget as f64) as u64;
+    debug_simulate(&format!(
+        "[estimate_gas_budget] cost={cost}, margin={safety_margin}, budget={budget}"
+    ));
+    Ok(budget)
+}
+
+impl SimulationReport {
+    pub fn from_executed(
+        transaction: ExecutedTransaction,
+        context: &AbortContext,
+    ) -> Result<SimulationReport, QuantError> {
+        let outcome = ExecutionOutcome::from_executed(&transaction, context)?;
+        let balance_changes = transaction
+            .balance_changes
+            .iter()
+            .map(BalanceDelta::from_proto)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SimulationReport {
+            outcome,
+            balance_changes,
+            transaction,
+        })
+    }
+
+    /// Net balance change of `coin_type` for `address`.
+    pub fn balance_change(&self, address: &Address, coin_type: &TypeTag) -> i128 {
+        self.balance_changes
+            .iter()
+            .filter(|delta| delta.is_for(address, coin_type))
+            .map(|delta| delta.amount)
+            .sum()
+    }
+
+    /// Fail if the simulated transaction failed.
+    pub fn ensure_success(&self) -> Result<(), QuantError> {
+        self.outcome.ensure_success()
+    }
+
+    /// Fail if `address` receives less than `min_amount_out` of `coin_type`.
+    pub fn ensure_min_output(
+        &self,
+        address: &Address,
+        coin_type: &TypeTag,
+        min_amount_out: u64,
+    ) -> Result<(), QuantError> {
+        let received = self.balance_change(address, coin_type);
+        if received < min_amount_out as i128 {
+            return Err(QuantError::OutputBelowMinimum {
+                coin_type: coin_type.to_string(),
+                received,
+                min_amount_out,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn print(&self) {
+        status!("Simulation:");
+        self.outcome.print();
+        for delta in &self.balance_changes {
+            status!(
+                "Balance change: {} {} {}",
+                delta.address,
+                delta.coin_type,
+                delta.amount
+            );
+        }
+        if let Some(effects) = &self.transaction.effects {
+            debug_simulate(&format!("[SimulationReport::print] effects: {effects:?}"));
+        }
+    }
+}
+
+impl GasUsed {
+    pub fn from_proto(summary: &GasCostSummary) -> GasUsed {
+        GasUsed {
+            computation_cost: summary.computation_cost.unwrap_or(0),
+            storage_cost: summary.storage_cost.unwrap_or(0),
+            storage_rebate: summary.storage_rebate.unwrap_or(0),
+            non_refundable_storage_fee: summary.non_refundable_storage_fee.unwrap_or(0),
+        }
+    }
+
+    /// Computation plus storage, minus the storage rebate.
+    pub fn net_cost(&self) -> i128 {
+        self.computation_cost as i128 + self.storage_cost as i128 - self.storage_rebate as i128
+    }
+}
+
+impl BalanceDelta {
+    pub fn from_proto(change: &BalanceChange) -> Result<BalanceDelta, QuantError> {
+        let amount = change
+            .amount
+            .as_deref()
+            .ok_or_else(|| QuantError::invalid_response("Balance change without amount"))?;
+        Ok(BalanceDelta {
+            address: change.address.clone().unwrap_or_default(),
+            coin_type: change.coin_type.clone().unwrap_or_default(),
+            amount: error::parse(amount, "balance change amount")?,
+        })
+    }
+
+    fn is_for(&self, address: &Address, coin_type: &TypeTag) -> bool {
+        self.address.parse::<Address>().ok().as_ref() == Some(address)
+            && self.coin_type.parse::<TypeTag>().ok().as_ref() == Some(coin_type)
+    }
+}
+
+fn debug_simulate(msg: &str) {
+    if DEBUG_SIMULATE {
+        eprintln!("{msg}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gas_used(computation_cost: u64, storage_cost: u64) -> GasUsed {
+        GasUsed {
+            computation_cost,
+            storage_cost,
+            storage_rebate: 900_000,
+            ..GasUsed::default()
+        }
+    }
+
+    #[test]
+    fn estimate_gas_budget_applies_the_margin_to_the_gross_cost() {
+        let gas_used = gas_used(1_000_000, 2_000_001);
+        assert_eq!(
+            estimate_gas_budget(&gas_used, 1.0, 50_000_000).unwrap(),
+            3_000_001
+        );
+        assert_eq!(
+            estimate_gas_budget(&gas_used, 1.5, 50_000_000).unwrap(),
+            4_500_002
+        );
+    }
+
+    #[test]
+    fn estimate_gas_budget_caps_the_margin_at_the_maximum() {
+        let gas_used = gas_used(1_000_000, 2_000_000);
+        assert_eq!(
+            estimate_gas_budget(&gas_used, 2.0, 4_000_000).unwrap(),
+            4_000_000
+        );
+    }
+
+    #[test]
+    fn estimate_gas_budget_rejects_a_cost_above_the_maximum() {
+        let gas_used = gas_used(1_000_000, 2_000_000);
+        assert!(estimate_gas_budget(&gas_used, 1.0, 2_999_999).is_err());
+    }
+
+    #[test]
+    fn estimate_gas_budget_rejects_margins_below_one() {
+        let gas_used = gas_used(1_000_000, 2_000_000);
+        assert!(estimate_gas_budget(&gas_used, 0.9, 50_000_000).is_err());
+        assert!(estimate_gas_budget(&gas_used, f64::NAN, 50_000_000).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use crate::error::QuantError;
+use crate::quote::PoolState;
+
+/// Protocol bounds of a pool's sqrt_price (Q64.64).
+pub const MIN_SQRT_PRICE: u128 = 4_295_048_016;
+pub const MAX_SQRT_PRICE: u128 = 79_226_673_515_401_279_992_447_579_055;
+
+/// Basis points in 100%.
+const BPS_DENOMINATOR: f64 = 10_000.0;
+
+/// Slippage protection requested by the caller.
+#[derive(Clone, Copy, Debug)]
+pub enum Slippage {
+    /// Maximum price move, in basis points of the current pool price.
+    ToleranceBps(u32),
+    /// Explicit minimum output amount; the price itself is not bounded.
+    MinAmountOut(u64),
+    /// Explicit maximum input amount for exact-output swaps; the price
+    /// itself is not bounded.
+    MaxAmountIn(u64),
+}
+
+/// Bounds passed to `flash_swap` / `assert_slippage` and checked on the output coin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlippageLimits {
+    /// Price the pool may not cross, for `flash_swap` and `assert_slippage`.
+    pub sqrt_price_limit: u128,
+    /// Minimum output amount, 0 to skip the check.
+    pub min_amount_out: u64,
+    /// Maximum input amount of exact-output swaps, `u64::MAX` if unbounded.
+    pub max_amount_in: u64,
+}
+
+impl SlippageLimits {
+    /// No protection: the protocol extremes and no minimum output.
+    pub fn unbounded(a_to_b: bool) -> SlippageLimits {
+        SlippageLimits {
+            sqrt_price_limit: extreme_sqrt_price(a_to_b),
+            min_amount_out: 0,
+            max_amount_in: u64::MAX,
+        }
+    }
+
+    /// Limits for selling `amount_in` to `pool` at its current price.
+    ///
+    /// With a tolerance, the price may move at most `bps` past the quoted
+    /// post-trade price, so the order's own price impact never stops it
+    /// halfway, and the output may be at most `bps` below the local quote,
+    /// which already pays the pool fee and the price impact. Only the
+    /// tolerance needs the pool's ticks.
+    pub fn new(
+        slippage: Slippage,
+        pool: &PoolState,
+        amount_in: u64,
+        a_to_b: bool,
+    ) -> Result<SlippageLimits, QuantError> {
+        check_sqrt_price(pool.sqrt_price)?;
+
+        match slippage {
+            Slippage::MinAmountOut(min_amount_out) => Ok(SlippageLimits {
+                min_amount_out,
+                ..SlippageLimits::unbounded(a_to_b)
+            }),
+            Slippage::MaxAmountIn(_) => {
+                Err(QuantError::InvalidInput(
+                    "A maximum input only applies to exact-output swaps".to_string(),
+                ))
+            }
+            Slippage::ToleranceBps(bps) => {
+                let tolerance = tolerance(bps)?;
+                let quote = pool.quote(amount_in, a_to_b);
+                if quote.amount_in < amount_in {
+                    return Err(QuantError::InvalidInput(format!(
+                        "Pool liquidity only absorbs {} of {amount_in}",
+                        quote.amount_in
+                    )));
+                }
+                let min_amount_out = (quote.amount_out as f64 * (1.0 - tolerance)).floor();
+
+                Ok(SlippageLimits {
+                    sqrt_price_limit: price_limit(quote.sqrt_price_after, tolerance, a_to_b),
+                    min_amount_out: min_amount_out as u64,
+                    max_amount_in: u64::MAX,
+                })
+            }
+        }
+    }
+
+    /// Limits for buying exactly `amount_out` from `pool` at its current
+    /// price.
+    ///
+    /// With a tolerance, the price may move at most `bps` past the quoted
+    /// post-trade price and the input may be at most `bps` above the locally
+    /// quoted input, fee and price impact included. The minimum output is
+    /// always `amount_out`: a flash swap stopped by the price limit fills
+    /// less, which must abort instead of buying a part of the order.
+    pub fn exact_out(
+        slippage: Slippage,
+        pool: &PoolState,
+        amount_out: u64,
+        a_to_b: bool,
+    ) -> Result<SlippageLimits, QuantError> {
+        check_sqrt_price(pool.sqrt_price)?;
+
+        match slippage {
+            Slippage::MaxAmountIn(max_amount_in) => Ok(SlippageLimits {
+                min_amount_out: amount_out,
+                max_amount_in,
+                ..SlippageLimits::unbounded(a_to_b)
+            }),
+            Slippage::MinAmountOut(_) => {
+                Err(QuantError::InvalidInput(
+                    "A minimum output does not apply to exact-output swaps".to_string(),
+                ))
+            }
+            Slippage::ToleranceBps(bps) => {
+                let tolerance = tolerance(bps)?;
+                let quote = pool.quote_exact_out(amount_out, a_to_b).ok_or_else(|| {
+                    QuantError::InvalidInput(format!("Pool liquidity cannot fill an output of {amount_out}"))
+                })?;
+                let max_amount_in = (quote.amount_in as f64 * (1.0 + tolerance)).ceil();
+                if max_amount_in >= u64::MAX as f64 {
+                    return Err(QuantError::InvalidInput("Maximum input overflows u64".to_string()));
+                }
+
+                Ok(SlippageLimits {
+                    sqrt_price_limit: price_limit(quote.sqrt_price_after, tolerance, a_to_b),
+                    min_amount_out: amount_out,
+                    max_amount_in: max_amount_in as u64,
+                })
+            }
+        }
+    }
+}
+
+/// Minimum final output of a multi-hop route, given each hop's pool and
+/// direction.
+///
+/// With a tolerance, the output may be at most `bps` below the output of
+/// chaining every hop's local quote, so the pool fees of all hops do not
+/// count against the tolerance.
+pub fn route_min_amount_out(
+    slippage: Slippage,
+    hops: &[(&PoolState, bool)],
+    amount_in: u64,
+) -> Result<u64, QuantError> {
+    match slippage {
+        Slippage::MinAmountOut(min_amount_out) => Ok(min_amount_out),
+        Slippage::MaxAmountIn(_) => {
+            Err(QuantError::InvalidInput(
+                "A maximum input only applies to exact-output swaps".to_string(),
+            ))
+        }
+        Slippage::ToleranceBps(bps) => {
+            let tolerance = tolerance(bps)?;
+            let mut expected_out = amount_in;
+            for (i, &(pool, a_to_b)) in hops.iter().enumerate() {
+                check_sqrt_price(pool.sqrt_price)?;
+                let quote = pool.quote(expected_out, a_to_b);
+                if quote.amount_in < expected_out {
+                    return Err(QuantError::InvalidInput(format!(
+                        "Hop {} liquidity only absorbs {} of {expected_out}",
+                        i + 1,
+                        quote.amount_in
+                    )));
+                }
+                expected_out = quote.amount_out;
+            }
+            Ok((expected_out as f64 * (1.0 - tolerance)).floor() as u64)
+        }
+    }
+}
+
+fn check_sqrt_price(sqrt_price: u128) -> Result<(), QuantError> {
+    if !(MIN_SQRT_PRICE..=MAX_SQRT_PRICE).contains(&sqrt_price) {
+        return Err(QuantError::InvalidResponse(
+            format!("Pool sqrt_price {sqrt_price} out of protocol range"),
+        ));
+    }
+    Ok(())
+}
+
+fn tolerance(bps: u32) -> Result<f64, QuantError> {
+    if bps as f64 >= BPS_DENOMINATOR {
+        return Err(QuantError::InvalidInput(
+            format!("Slippage tolerance {bps} bps must be below 100%"),
+        ));
+    }
+    Ok(bps as f64 / BPS_DENOMINATOR)
+}
+
+/// Sqrt price after a `tolerance` move: selling A lowers the price, selling
+/// B raises it.
+fn price_limit(sqrt_price: u128, tolerance: f64, a_to_b: bool) -> u128 {
+    let price_factor = if a_to_b { 1.0 - tolerance } else { 1.0 + tolerance };
+    let limit = (sqrt_price as f64 * price_factor.sqrt()) as u128;
+    limit.clamp(MIN_SQRT_PRICE, MAX_SQRT_PRICE)
+}
+
+fn extreme_sqrt_price(a_to_b: bool) -> u128 {
+    if a_to_b { MIN_SQRT_PRICE } else { MAX_SQRT_PRICE }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pool at price 1 with a 0.3% fee and no initialized ticks.
+    fn pool() -> PoolState {
+        PoolState::at_price_one(1_000_000_000_000, 3_000, &[])
+    }
+
+    #[test]
+    fn new_keeps_an_explicit_minimum_output() {
+        let limits = SlippageLimits::new(Slippage::MinAmountOut(5), &pool(), 1_000, true).unwrap();
+        assert_eq!(limits, SlippageLimits { min_amount_out: 5, ..SlippageLimits::unbounded(true) });
+    }
+
+    #[test]
+    fn new_applies_the_tolerance_to_the_quote() {
+        let pool = pool();
+        let limits = SlippageLimits::new(Slippage::ToleranceBps(100), &pool, 1_000_000, true).unwrap();
+
+        // The fee is in the quote, so a 1% tolerance accepts 99% of it.
+        let quoted = pool.quote(1_000_000, true).amount_out;
+        assert_eq!(limits.min_amount_out, (quoted as f64 * 0.99).floor() as u64);
+        assert!(limits.min_amount_out > 980_000);
+        assert!(limits.sqrt_price_limit < pool.sqrt_price);
+        assert_eq!(limits.max_amount_in, u64::MAX);
+
+        let limits = SlippageLimits::new(Slippage::ToleranceBps(100), &pool, 1_000_000, false).unwrap();
+        assert!(limits.sqrt_price_limit > pool.sqrt_price);
+    }
+
+    #[test]
+    fn new_rejects_invalid_requests() {
+        let pool = pool();
+        assert!(SlippageLimits::new(Slippage::MaxAmountIn(5), &pool, 1_000, true).is_err());
+        assert!(SlippageLimits::new(Slippage::ToleranceBps(10_000), &pool, 1_000, true).is_err());
+
+        let out_of_range = PoolState { sqrt_price: MIN_SQRT_PRICE - 1, ..pool };
+        assert!(SlippageLimits::new(Slippage::MinAmountOut(5), &out_of_range, 1_000, true).is_err());
+    }
+
+    #[test]
+    fn new_rejects_inputs_beyond_liquidity() {
+        let mut pool = pool();
+        pool.ticks.insert(-10, pool.liquidity as i128);
+        assert!(SlippageLimits::new(Slippage::ToleranceBps(100), &pool, 1_000_000_000, true).is_err());
+    }
+
+    #[test]
+    fn exact_out_requires_the_full_output() {
+        let limits = SlippageLimits::exact_out(Slippage::MaxAmountIn(7_000), &pool(), 5_000, true).unwrap();
+        assert_eq!(limits.min_amount_out, 5_000);
+        assert_eq!(limits.max_amount_in, 7_000);
+        assert_eq!(limits.sqrt_price_limit, MIN_SQRT_PRICE);
+
+        let limits = SlippageLimits::exact_out(Slippage::ToleranceBps(100), &pool(), 5_000, false).unwrap();
+        assert_eq!(limits.min_amount_out, 5_000);
+        let after = pool().quote_exact_out(5_000, false).unwrap().sqrt_price_after;
+        assert_eq!(limits.sqrt_price_limit, price_limit(after, 0.01, false));
+    }
+
+    #[test]
+    fn limits_leave_room_for_the_order_price_impact() {
+        // A 5% order moves the price by about 10%, far past a 1% tolerance.
+        let pool = pool();
+        let quote = pool.quote(50_000_000_000, true);
+        let limits = SlippageLimits::new(Slippage::ToleranceBps(100), &pool, 50_000_000_000, true).unwrap();
+        assert!(limits.sqrt_price_limit < quote.sqrt_price_after);
+
+        let quote = pool.quote_exact_out(50_000_000_000, false).unwrap();
+        let limits = SlippageLimits::exact_out(Slippage::ToleranceBps(100), &pool, 50_000_000_000, false).unwrap();
+        assert!(limits.sqrt_price_limit > quote.sqrt_price_after);
+    }
+
+    #[test]
+    fn exact_out_applies_the_tolerance_to_the_quoted_input() {
+        let pool = pool();
+        let limits = SlippageLimits::exact_out(Slippage::ToleranceBps(100), &pool, 1_000_000, true).unwrap();
+        let quoted = pool.quote_exact_out(1_000_000, true).unwrap().amount_in;
+        assert_eq!(limits.max_amount_in, (quoted as f64 * 1.01).ceil() as u64);
+        assert!(quoted > 1_003_000);
+    }
+
+    #[test]
+    fn exact_out_rejects_invalid_requests() {
+        let pool = pool();
+        assert!(SlippageLimits::exact_out(Slippage::MinAmountOut(5), &pool, 1_000, true).is_err());
+
+        let mut shallow = pool.clone();
+        shallow.ticks.insert(-10, shallow.liquidity as i128);
+        assert!(SlippageLimits::exact_out(Slippage::ToleranceBps(100), &shallow, 600_000_000, true).is_err());
+    }
+
+    #[test]
+    fn route_min_amount_out_chains_the_hop_quotes() {
+        let first = pool();
+        let second = PoolState { fee_rate: 500, ..pool() };
+        let min_amount_out =
+            route_min_amount_out(Slippage::ToleranceBps(50), &[(&first, true), (&second, false)], 1_000_000).unwrap();
+
+        // Both fees are in the chained quote, only the 0.5% is slippage.
+        let middle = first.quote(1_000_000, true).amount_out;
+        let quoted = second.quote(middle, false).amount_out;
+        assert_eq!(min_amount_out, (quoted as f64 * 0.995).floor() as u64);
+        assert!(min_amount_out > 990_000);
+    }
+
+    #[test]
+    fn route_min_amount_out_rejects_a_hop_beyond_liquidity() {
+        let deep = pool();
+        let mut shallow = pool();
+        shallow.ticks.insert(10, -(shallow.liquidity as i128));
+        let hops = [(&deep, true), (&shallow, false)];
+        assert!(route_min_amount_out(Slippage::ToleranceBps(50), &hops, 1_000_000_000).is_err());
+        assert_eq!(route_min_amount_out(Slippage::MinAmountOut(7), &hops, 1_000_000_000).unwrap(), 7);
+    }
+}